@@ -2,6 +2,10 @@ use libc::*;
 
 use *;
 
+pub const V_ASN1_UNDEF: c_int = -1;
+pub const V_ASN1_BOOLEAN: c_int = 1;
+pub const V_ASN1_NULL: c_int = 5;
+pub const V_ASN1_OBJECT: c_int = 6;
 pub const V_ASN1_UTCTIME: c_int = 23;
 pub const V_ASN1_GENERALIZEDTIME: c_int = 24;
 
@@ -35,6 +39,7 @@ extern "C" {
 
     pub fn ASN1_STRING_free(x: *mut ASN1_STRING);
     pub fn ASN1_STRING_length(x: *const ASN1_STRING) -> c_int;
+    pub fn ASN1_STRING_set(x: *mut ASN1_STRING, data: *const c_void, len: c_int) -> c_int;
 
     pub fn ASN1_GENERALIZEDTIME_free(tm: *mut ASN1_GENERALIZEDTIME);
     pub fn ASN1_GENERALIZEDTIME_print(b: *mut BIO, tm: *const ASN1_GENERALIZEDTIME) -> c_int;
@@ -48,6 +53,18 @@ extern "C" {
     pub fn ASN1_INTEGER_to_BN(ai: *const ASN1_INTEGER, bn: *mut BIGNUM) -> *mut BIGNUM;
 }
 
+cfg_if! {
+    if #[cfg(ossl110)] {
+        extern "C" {
+            pub fn ASN1_TYPE_get(a: *const ASN1_TYPE) -> c_int;
+        }
+    } else {
+        extern "C" {
+            pub fn ASN1_TYPE_get(a: *mut ASN1_TYPE) -> c_int;
+        }
+    }
+}
+
 cfg_if! {
     if #[cfg(any(ossl110, libressl280))] {
         extern "C" {
@@ -6,6 +6,7 @@ pub const BIO_TYPE_NONE: c_int = 0;
 
 pub const BIO_CTRL_EOF: c_int = 2;
 pub const BIO_CTRL_INFO: c_int = 3;
+pub const BIO_CTRL_PENDING: c_int = 10;
 pub const BIO_CTRL_FLUSH: c_int = 11;
 pub const BIO_C_SET_BUF_MEM_EOF_RETURN: c_int = 130;
 
@@ -59,6 +60,10 @@ pub unsafe fn BIO_get_mem_data(b: *mut BIO, pp: *mut *mut c_char) -> c_long {
     BIO_ctrl(b, BIO_CTRL_INFO, 0, pp as *mut c_void)
 }
 
+pub unsafe fn BIO_set_mem_eof_return(b: *mut BIO, v: c_int) -> c_long {
+    BIO_ctrl(b, BIO_C_SET_BUF_MEM_EOF_RETURN, v as c_long, ::std::ptr::null_mut())
+}
+
 cfg_if! {
     if #[cfg(any(ossl110, libressl280))] {
         extern "C" {
@@ -84,9 +89,16 @@ extern "C" {
     pub fn BIO_write(b: *mut BIO, buf: *const c_void, len: c_int) -> c_int;
     pub fn BIO_read(b: *mut BIO, buf: *mut c_void, len: c_int) -> c_int;
     pub fn BIO_ctrl(b: *mut BIO, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
+    pub fn BIO_ctrl_pending(b: *mut BIO) -> size_t;
     pub fn BIO_free_all(b: *mut BIO);
 }
 
+#[cfg(ossl110)]
+extern "C" {
+    pub fn BIO_ADDR_new() -> *mut BIO_ADDR;
+    pub fn BIO_ADDR_free(addr: *mut BIO_ADDR);
+}
+
 cfg_if! {
     if #[cfg(any(ossl110, libressl280))] {
         extern "C" {
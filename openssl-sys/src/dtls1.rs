@@ -1,3 +1,22 @@
 use libc::*;
+use std::ptr;
+
+use *;
 
 pub const DTLS1_COOKIE_LENGTH: c_uint = 256;
+
+pub const DTLS_CTRL_GET_TIMEOUT: c_int = 73;
+pub const DTLS_CTRL_HANDLE_TIMEOUT: c_int = 74;
+
+pub unsafe fn DTLSv1_get_timeout(s: *mut SSL, tv: *mut timeval) -> c_long {
+    SSL_ctrl(s, DTLS_CTRL_GET_TIMEOUT, 0, tv as *mut c_void)
+}
+
+pub unsafe fn DTLSv1_handle_timeout(s: *mut SSL) -> c_long {
+    SSL_ctrl(s, DTLS_CTRL_HANDLE_TIMEOUT, 0, ptr::null_mut())
+}
+
+extern "C" {
+    #[cfg(ossl110)]
+    pub fn DTLSv1_listen(s: *mut SSL, client: *mut BIO_ADDR) -> c_int;
+}
@@ -51,6 +51,8 @@ extern "C" {
 
     pub fn EC_GROUP_get_degree(group: *const EC_GROUP) -> c_int;
 
+    pub fn EC_GROUP_get_curve_name(group: *const EC_GROUP) -> c_int;
+
     pub fn EC_GROUP_new_curve_GFp(
         p: *const BIGNUM,
         a: *const BIGNUM,
@@ -13,6 +13,8 @@ pub enum ASN1_OBJECT {}
 pub enum ASN1_OCTET_STRING {}
 
 pub enum bio_st {} // FIXME remove
+#[cfg(ossl110)]
+pub enum BIO_ADDR {}
 cfg_if! {
     if #[cfg(any(ossl110, libressl280))] {
         pub enum BIO {}
@@ -341,6 +343,7 @@ cfg_if! {
 }
 pub enum X509_CRL {}
 pub enum X509_NAME {}
+pub enum X509_PUBKEY {}
 pub enum X509_STORE {}
 pub enum X509_STORE_CTX {}
 
@@ -8,6 +8,8 @@ pub const SRTP_AES128_F8_SHA1_80: c_ulong = 0x0003;
 pub const SRTP_AES128_F8_SHA1_32: c_ulong = 0x0004;
 pub const SRTP_NULL_SHA1_80: c_ulong = 0x0005;
 pub const SRTP_NULL_SHA1_32: c_ulong = 0x0006;
+pub const SRTP_AEAD_AES_128_GCM: c_ulong = 0x0007;
+pub const SRTP_AEAD_AES_256_GCM: c_ulong = 0x0008;
 
 extern "C" {
     pub fn SSL_CTX_set_tlsext_use_srtp(ctx: *mut SSL_CTX, profiles: *const c_char) -> c_int;
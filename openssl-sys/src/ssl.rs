@@ -421,6 +421,9 @@ cfg_if! {
             pub fn SSL_CTX_get_options(ctx: *const SSL_CTX) -> c_ulong;
             pub fn SSL_CTX_set_options(ctx: *mut SSL_CTX, op: c_ulong) -> c_ulong;
             pub fn SSL_CTX_clear_options(ctx: *mut SSL_CTX, op: c_ulong) -> c_ulong;
+            pub fn SSL_get_options(ssl: *const SSL) -> c_ulong;
+            pub fn SSL_set_options(ssl: *mut SSL, op: c_ulong) -> c_ulong;
+            pub fn SSL_clear_options(ssl: *mut SSL, op: c_ulong) -> c_ulong;
         }
     } else {
         pub unsafe fn SSL_CTX_get_options(ctx: *const SSL_CTX) -> c_ulong {
@@ -444,6 +447,18 @@ cfg_if! {
                 ptr::null_mut(),
             ) as c_ulong
         }
+
+        pub unsafe fn SSL_get_options(ssl: *const SSL) -> c_ulong {
+            SSL_ctrl(ssl as *mut _, SSL_CTRL_OPTIONS, 0, ptr::null_mut()) as c_ulong
+        }
+
+        pub unsafe fn SSL_set_options(ssl: *mut SSL, op: c_ulong) -> c_ulong {
+            SSL_ctrl(ssl, SSL_CTRL_OPTIONS, op as c_long, ptr::null_mut()) as c_ulong
+        }
+
+        pub unsafe fn SSL_clear_options(ssl: *mut SSL, op: c_ulong) -> c_ulong {
+            SSL_ctrl(ssl, SSL_CTRL_CLEAR_OPTIONS, op as c_long, ptr::null_mut()) as c_ulong
+        }
     }
 }
 
@@ -619,8 +634,30 @@ extern "C" {
             extern "C" fn(*mut SSL, *const c_char, *mut c_uchar, c_uint) -> c_uint,
         >,
     );
+    pub fn SSL_CTX_use_psk_identity_hint(ctx: *mut SSL_CTX, hint: *const c_char) -> c_int;
+    pub fn SSL_use_psk_identity_hint(ssl: *mut SSL, hint: *const c_char) -> c_int;
 }
 
+#[cfg(ossl111)]
+pub type SSL_psk_use_session_cb_func = Option<
+    unsafe extern "C" fn(
+        ssl: *mut SSL,
+        md: *const EVP_MD,
+        id: *mut *const c_uchar,
+        idlen: *mut size_t,
+        sess: *mut *mut SSL_SESSION,
+    ) -> c_int,
+>;
+#[cfg(ossl111)]
+pub type SSL_psk_find_session_cb_func = Option<
+    unsafe extern "C" fn(
+        ssl: *mut SSL,
+        identity: *const c_uchar,
+        identity_len: size_t,
+        sess: *mut *mut SSL_SESSION,
+    ) -> c_int,
+>;
+
 extern "C" {
     #[cfg(ossl111)]
     pub fn SSL_CTX_add_custom_ext(
@@ -638,6 +675,52 @@ extern "C" {
     pub fn SSL_extension_supported(ext_type: c_uint) -> c_int;
 }
 
+pub const SSL_ST_CONNECT: c_int = 0x1000;
+pub const SSL_ST_ACCEPT: c_int = 0x2000;
+
+pub const SSL_CB_LOOP: c_int = 0x01;
+pub const SSL_CB_EXIT: c_int = 0x02;
+pub const SSL_CB_READ: c_int = 0x04;
+pub const SSL_CB_WRITE: c_int = 0x08;
+pub const SSL_CB_ALERT: c_int = 0x4000;
+pub const SSL_CB_READ_ALERT: c_int = SSL_CB_ALERT | SSL_CB_READ;
+pub const SSL_CB_WRITE_ALERT: c_int = SSL_CB_ALERT | SSL_CB_WRITE;
+pub const SSL_CB_ACCEPT_LOOP: c_int = SSL_ST_ACCEPT | SSL_CB_LOOP;
+pub const SSL_CB_ACCEPT_EXIT: c_int = SSL_ST_ACCEPT | SSL_CB_EXIT;
+pub const SSL_CB_CONNECT_LOOP: c_int = SSL_ST_CONNECT | SSL_CB_LOOP;
+pub const SSL_CB_CONNECT_EXIT: c_int = SSL_ST_CONNECT | SSL_CB_EXIT;
+pub const SSL_CB_HANDSHAKE_START: c_int = 0x10;
+pub const SSL_CB_HANDSHAKE_DONE: c_int = 0x20;
+
+pub const SSL_CTRL_SET_MSG_CALLBACK_ARG: c_int = 16;
+
+pub unsafe fn SSL_CTX_set_msg_callback_arg(ctx: *mut SSL_CTX, arg: *mut c_void) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SET_MSG_CALLBACK_ARG, 0, arg)
+}
+
+extern "C" {
+    pub fn SSL_CTX_set_msg_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<
+            unsafe extern "C" fn(
+                write_p: c_int,
+                version: c_int,
+                content_type: c_int,
+                buf: *const c_void,
+                len: size_t,
+                ssl: *mut SSL,
+                arg: *mut c_void,
+            ),
+        >,
+    );
+    pub fn SSL_CTX_set_info_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<unsafe extern "C" fn(ssl: *const SSL, type_: c_int, val: c_int)>,
+    );
+    pub fn SSL_alert_type_string_long(value: c_int) -> *const c_char;
+    pub fn SSL_alert_desc_string_long(value: c_int) -> *const c_char;
+}
+
 #[cfg(ossl111)]
 pub type SSL_CTX_keylog_cb_func =
     Option<unsafe extern "C" fn(ssl: *const SSL, line: *const c_char)>;
@@ -659,6 +742,8 @@ extern "C" {
     pub fn SSL_get_peer_finished(s: *const SSL, buf: *mut c_void, count: size_t) -> size_t;
 }
 
+pub const SSL_AD_CLOSE_NOTIFY: c_int = SSL3_AD_CLOSE_NOTIFY;
+pub const SSL_AD_HANDSHAKE_FAILURE: c_int = SSL3_AD_HANDSHAKE_FAILURE;
 pub const SSL_AD_ILLEGAL_PARAMETER: c_int = SSL3_AD_ILLEGAL_PARAMETER;
 pub const SSL_AD_DECODE_ERROR: c_int = TLS1_AD_DECODE_ERROR;
 pub const SSL_AD_UNRECOGNIZED_NAME: c_int = TLS1_AD_UNRECOGNIZED_NAME;
@@ -676,15 +761,39 @@ pub const SSL_ERROR_WANT_CLIENT_HELLO_CB: c_int = 11;
 pub const SSL_VERIFY_NONE: c_int = 0;
 pub const SSL_VERIFY_PEER: c_int = 1;
 pub const SSL_VERIFY_FAIL_IF_NO_PEER_CERT: c_int = 2;
+#[cfg(ossl111)]
+pub const SSL_VERIFY_POST_HANDSHAKE: c_int = 0x08;
+
+#[cfg(ossl111)]
+pub const SSL_KEY_UPDATE_NONE: c_int = -1;
+#[cfg(ossl111)]
+pub const SSL_KEY_UPDATE_NOT_REQUESTED: c_int = 0;
+#[cfg(ossl111)]
+pub const SSL_KEY_UPDATE_REQUESTED: c_int = 1;
 pub const SSL_CTRL_SET_TMP_DH: c_int = 3;
 pub const SSL_CTRL_SET_TMP_ECDH: c_int = 4;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_GET_SESSION_REUSED: c_int = 8;
 pub const SSL_CTRL_EXTRA_CHAIN_CERT: c_int = 14;
+pub const SSL_CTRL_SET_MTU: c_int = 17;
+pub const SSL_CTRL_SESS_NUMBER: c_int = 20;
+pub const SSL_CTRL_SESS_CONNECT: c_int = 21;
+pub const SSL_CTRL_SESS_CONNECT_GOOD: c_int = 22;
+pub const SSL_CTRL_SESS_CONNECT_RENEGOTIATE: c_int = 23;
+pub const SSL_CTRL_SESS_ACCEPT: c_int = 24;
+pub const SSL_CTRL_SESS_ACCEPT_GOOD: c_int = 25;
+pub const SSL_CTRL_SESS_ACCEPT_RENEGOTIATE: c_int = 26;
+pub const SSL_CTRL_SESS_HIT: c_int = 27;
+pub const SSL_CTRL_SESS_CB_HIT: c_int = 28;
+pub const SSL_CTRL_SESS_MISSES: c_int = 29;
+pub const SSL_CTRL_SESS_TIMEOUTS: c_int = 30;
+pub const SSL_CTRL_SESS_CACHE_FULL: c_int = 31;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_OPTIONS: c_int = 32;
 pub const SSL_CTRL_MODE: c_int = 33;
 pub const SSL_CTRL_SET_READ_AHEAD: c_int = 41;
+pub const SSL_CTRL_SET_SESS_CACHE_SIZE: c_int = 42;
+pub const SSL_CTRL_GET_SESS_CACHE_SIZE: c_int = 43;
 pub const SSL_CTRL_SET_SESS_CACHE_MODE: c_int = 44;
 pub const SSL_CTRL_SET_TLSEXT_SERVERNAME_CB: c_int = 53;
 pub const SSL_CTRL_SET_TLSEXT_SERVERNAME_ARG: c_int = 54;
@@ -694,9 +803,32 @@ pub const SSL_CTRL_SET_TLSEXT_STATUS_REQ_CB_ARG: c_int = 64;
 pub const SSL_CTRL_SET_TLSEXT_STATUS_REQ_TYPE: c_int = 65;
 pub const SSL_CTRL_GET_TLSEXT_STATUS_REQ_OCSP_RESP: c_int = 70;
 pub const SSL_CTRL_SET_TLSEXT_STATUS_REQ_OCSP_RESP: c_int = 71;
+pub const SSL_CTRL_SET_TLSEXT_TICKET_KEY_CB: c_int = 72;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_CLEAR_OPTIONS: c_int = 77;
 pub const SSL_CTRL_GET_EXTRA_CHAIN_CERTS: c_int = 82;
+#[cfg(ossl102)]
+pub const SSL_CTRL_CHAIN: c_int = 88;
+#[cfg(ossl102)]
+pub const SSL_CTRL_CHAIN_CERT: c_int = 89;
+#[cfg(ossl102)]
+pub const SSL_CTRL_BUILD_CERT_CHAIN: c_int = 105;
+#[cfg(ossl102)]
+pub const SSL_CTRL_GET_CHAIN_CERTS: c_int = 115;
+#[cfg(ossl102)]
+pub const SSL_CTRL_SELECT_CURRENT_CERT: c_int = 116;
+#[cfg(ossl102)]
+pub const SSL_CTRL_SET_CURRENT_CERT: c_int = 117;
+#[cfg(ossl102)]
+pub const SSL_CTRL_SET_GROUPS_LIST: c_int = 92;
+#[cfg(ossl102)]
+pub const SSL_CTRL_SET_SIGALGS_LIST: c_int = 98;
+#[cfg(ossl102)]
+pub const SSL_CTRL_SET_CLIENT_SIGALGS_LIST: c_int = 102;
+#[cfg(ossl102)]
+pub const SSL_CTRL_GET_PEER_SIGNATURE_NID: c_int = 108;
+#[cfg(ossl102)]
+pub const SSL_CTRL_GET_PEER_TMP_KEY: c_int = 109;
 #[cfg(any(libressl, all(ossl102, not(ossl110))))]
 pub const SSL_CTRL_SET_ECDH_AUTO: c_int = 94;
 #[cfg(ossl102)]
@@ -726,10 +858,102 @@ pub unsafe fn SSL_set_tmp_ecdh(ssl: *mut SSL, key: *mut EC_KEY) -> c_long {
     SSL_ctrl(ssl, SSL_CTRL_SET_TMP_ECDH, 0, key as *mut c_void)
 }
 
+pub unsafe fn SSL_set_mtu(ssl: *mut SSL, mtu: c_long) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_SET_MTU, mtu, ptr::null_mut())
+}
+
 pub unsafe fn SSL_CTX_add_extra_chain_cert(ctx: *mut SSL_CTX, x509: *mut X509) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_EXTRA_CHAIN_CERT, 0, x509 as *mut c_void)
 }
 
+#[cfg(ossl102)]
+pub unsafe fn SSL_add1_chain_cert(ssl: *mut SSL, x509: *mut X509) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_CHAIN_CERT, 1, x509 as *mut c_void)
+}
+
+#[cfg(ossl111)]
+pub const SSL_EARLY_DATA_NOT_SENT: c_int = 0;
+#[cfg(ossl111)]
+pub const SSL_EARLY_DATA_REJECTED: c_int = 1;
+#[cfg(ossl111)]
+pub const SSL_EARLY_DATA_ACCEPTED: c_int = 2;
+
+#[cfg(ossl102)]
+pub const SSL_CERT_SET_FIRST: c_long = 1;
+#[cfg(ossl102)]
+pub const SSL_CERT_SET_NEXT: c_long = 2;
+#[cfg(ossl102)]
+pub const SSL_CERT_SET_SERVER: c_long = 3;
+
+#[cfg(ossl102)]
+pub const SSL_BUILD_CHAIN_FLAG_UNTRUSTED: c_long = 0x1;
+#[cfg(ossl102)]
+pub const SSL_BUILD_CHAIN_FLAG_NO_ROOT: c_long = 0x2;
+#[cfg(ossl102)]
+pub const SSL_BUILD_CHAIN_FLAG_CHECK: c_long = 0x4;
+#[cfg(ossl102)]
+pub const SSL_BUILD_CHAIN_FLAG_IGNORE_ERROR: c_long = 0x8;
+#[cfg(ossl102)]
+pub const SSL_BUILD_CHAIN_FLAG_CLEAR_ERROR: c_long = 0x10;
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_add1_chain_cert(ctx: *mut SSL_CTX, x509: *mut X509) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_CHAIN_CERT, 1, x509 as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_clear_chain_certs(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_CHAIN, 0, ptr::null_mut())
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_get0_chain_certs(
+    ctx: *mut SSL_CTX,
+    chain: *mut *mut stack_st_X509,
+) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_GET_CHAIN_CERTS, 0, chain as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_select_current_cert(ctx: *mut SSL_CTX, x509: *mut X509) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SELECT_CURRENT_CERT, 0, x509 as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_set_current_cert(ctx: *mut SSL_CTX, op: c_long) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SET_CURRENT_CERT, op, ptr::null_mut())
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_build_cert_chain(ctx: *mut SSL_CTX, flags: c_long) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_BUILD_CERT_CHAIN, flags, ptr::null_mut())
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_set1_groups_list(ctx: *mut SSL_CTX, s: *const c_char) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SET_GROUPS_LIST, 0, s as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_set1_sigalgs_list(ctx: *mut SSL_CTX, s: *const c_char) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SET_SIGALGS_LIST, 0, s as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_set1_client_sigalgs_list(ctx: *mut SSL_CTX, s: *const c_char) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SET_CLIENT_SIGALGS_LIST, 0, s as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_get_peer_signature_nid(ssl: *mut SSL, nid: *mut c_int) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_PEER_SIGNATURE_NID, 0, nid as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_get_peer_tmp_key(ssl: *mut SSL, key: *mut *mut EVP_PKEY) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_PEER_TMP_KEY, 0, key as *mut c_void)
+}
+
 pub unsafe fn SSL_CTX_get_extra_chain_certs(
     ctx: *mut SSL_CTX,
     chain: *mut *mut stack_st_X509,
@@ -742,6 +966,11 @@ pub unsafe fn SSL_CTX_set0_verify_cert_store(ctx: *mut SSL_CTX, st: *mut X509_ST
     SSL_CTX_ctrl(ctx, SSL_CTRL_SET_VERIFY_CERT_STORE, 0, st as *mut c_void)
 }
 
+#[cfg(ossl102)]
+pub unsafe fn SSL_set0_verify_cert_store(ssl: *mut SSL, st: *mut X509_STORE) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_SET_VERIFY_CERT_STORE, 0, st as *mut c_void)
+}
+
 #[cfg(any(libressl, all(ossl102, not(ossl110))))]
 pub unsafe fn SSL_CTX_set_ecdh_auto(ctx: *mut SSL_CTX, onoff: c_int) -> c_int {
     SSL_CTX_ctrl(
@@ -864,6 +1093,8 @@ extern "C" {
     #[cfg(ossl111)]
     pub fn SSL_CIPHER_get_handshake_digest(cipher: *const ::SSL_CIPHER) -> *const ::EVP_MD;
     pub fn SSL_CIPHER_get_name(cipher: *const SSL_CIPHER) -> *const c_char;
+    #[cfg(ossl111)]
+    pub fn SSL_CIPHER_standard_name(cipher: *const SSL_CIPHER) -> *const c_char;
 
     pub fn SSL_pending(ssl: *const SSL) -> c_int;
     pub fn SSL_set_bio(ssl: *mut SSL, rbio: *mut BIO, wbio: *mut BIO);
@@ -873,6 +1104,7 @@ extern "C" {
     pub fn SSL_CTX_set_ciphersuites(ctx: *mut SSL_CTX, str: *const c_char) -> c_int;
     #[cfg(ossl111)]
     pub fn SSL_set_ciphersuites(ssl: *mut ::SSL, str: *const c_char) -> c_int;
+    pub fn SSL_set_cipher_list(ssl: *mut SSL, s: *const c_char) -> c_int;
     pub fn SSL_set_verify(
         ssl: *mut SSL,
         mode: c_int,
@@ -881,6 +1113,8 @@ extern "C" {
     );
     pub fn SSL_CTX_use_PrivateKey(ctx: *mut SSL_CTX, key: *mut EVP_PKEY) -> c_int;
     pub fn SSL_CTX_use_certificate(ctx: *mut SSL_CTX, cert: *mut X509) -> c_int;
+    pub fn SSL_use_PrivateKey(ssl: *mut SSL, key: *mut EVP_PKEY) -> c_int;
+    pub fn SSL_use_certificate(ssl: *mut SSL, cert: *mut X509) -> c_int;
 
     pub fn SSL_CTX_use_PrivateKey_file(
         ctx: *mut SSL_CTX,
@@ -909,9 +1143,52 @@ extern "C" {
     pub fn SSL_SESSION_get_max_early_data(ctx: *const SSL_SESSION) -> u32;
 
     pub fn SSL_SESSION_get_id(s: *const SSL_SESSION, len: *mut c_uint) -> *const c_uchar;
+    pub fn SSL_SESSION_get_time(s: *const SSL_SESSION) -> c_long;
+    pub fn SSL_SESSION_set_time(s: *mut SSL_SESSION, t: c_long) -> c_long;
+    pub fn SSL_SESSION_get_timeout(s: *const SSL_SESSION) -> c_long;
+    pub fn SSL_SESSION_set_timeout(s: *mut SSL_SESSION, t: c_long) -> c_long;
+    pub fn SSL_SESSION_set1_id_context(
+        s: *mut SSL_SESSION,
+        sid_ctx: *const c_uchar,
+        sid_ctx_len: c_uint,
+    ) -> c_int;
+    pub fn SSL_SESSION_print(bp: *mut BIO, s: *const SSL_SESSION) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_get_protocol_version(s: *const SSL_SESSION) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_get0_cipher(s: *const SSL_SESSION) -> *const SSL_CIPHER;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_get0_peer(s: *mut SSL_SESSION) -> *mut X509;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_get_ticket_lifetime_hint(s: *const SSL_SESSION) -> c_ulong;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_has_ticket(s: *const SSL_SESSION) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_get0_hostname(s: *const SSL_SESSION) -> *const c_char;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_get0_alpn_selected(
+        s: *const SSL_SESSION,
+        alpn: *mut *const c_uchar,
+        len: *mut size_t,
+    );
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_is_resumable(s: *const SSL_SESSION) -> c_int;
     #[cfg(any(ossl110, libressl273))]
     pub fn SSL_SESSION_up_ref(ses: *mut SSL_SESSION) -> c_int;
     pub fn SSL_SESSION_free(s: *mut SSL_SESSION);
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_dup(src: *mut SSL_SESSION) -> *mut SSL_SESSION;
+    pub fn SSL_SESSION_new() -> *mut SSL_SESSION;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_set1_master_key(
+        s: *mut SSL_SESSION,
+        in_: *const c_uchar,
+        len: size_t,
+    ) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set_cipher(s: *mut SSL_SESSION, cipher: *const SSL_CIPHER) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set_protocol_version(s: *mut SSL_SESSION, version: c_int) -> c_int;
     pub fn i2d_SSL_SESSION(s: *mut SSL_SESSION, pp: *mut *mut c_uchar) -> c_int;
     pub fn SSL_set_session(ssl: *mut SSL, session: *mut SSL_SESSION) -> c_int;
     pub fn d2i_SSL_SESSION(
@@ -930,6 +1207,7 @@ extern "C" {
         verify_callback: Option<extern "C" fn(c_int, *mut X509_STORE_CTX) -> c_int>,
     );
     pub fn SSL_CTX_set_verify_depth(ctx: *mut SSL_CTX, depth: c_int);
+    pub fn SSL_set_verify_depth(ssl: *mut SSL, depth: c_int);
 
     pub fn SSL_CTX_check_private_key(ctx: *const SSL_CTX) -> c_int;
 
@@ -952,6 +1230,46 @@ pub const SSL_CLIENT_HELLO_ERROR: c_int = 0;
 #[cfg(ossl111)]
 pub const SSL_CLIENT_HELLO_RETRY: c_int = -1;
 
+extern "C" {
+    #[cfg(ossl102)]
+    pub fn SSL_CTX_set_cert_cb(
+        c: *mut SSL_CTX,
+        cb: Option<unsafe extern "C" fn(ssl: *mut SSL, arg: *mut c_void) -> c_int>,
+        arg: *mut c_void,
+    );
+    pub fn SSL_CTX_set_client_cert_cb(
+        c: *mut SSL_CTX,
+        cb: Option<
+            unsafe extern "C" fn(ssl: *mut SSL, x509: *mut *mut X509, pkey: *mut *mut EVP_PKEY)
+                -> c_int,
+        >,
+    );
+    #[cfg(ossl102)]
+    pub fn SSL_get_sigalgs(
+        s: *mut SSL,
+        idx: c_int,
+        psign: *mut c_int,
+        phash: *mut c_int,
+        psignhash: *mut c_int,
+        rsig: *mut c_uchar,
+        rhash: *mut c_uchar,
+    ) -> c_int;
+    #[cfg(ossl102)]
+    pub fn SSL_get_shared_sigalgs(
+        s: *mut SSL,
+        idx: c_int,
+        psign: *mut c_int,
+        phash: *mut c_int,
+        psignhash: *mut c_int,
+        rsig: *mut c_uchar,
+        rhash: *mut c_uchar,
+    ) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_get_peer_signature_type_nid(s: *const SSL, pnid: *mut c_int) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_get_client_ciphers(s: *const SSL) -> *mut stack_st_SSL_CIPHER;
+}
+
 #[cfg(ossl111)]
 pub type SSL_client_hello_cb_fn =
     Option<unsafe extern "C" fn(s: *mut SSL, al: *mut c_int, arg: *mut c_void) -> c_int>;
@@ -991,6 +1309,30 @@ extern "C" {
         outlen: *mut size_t,
     ) -> c_int;
 
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_post_handshake_auth(ctx: *mut SSL_CTX, val: c_int);
+    #[cfg(ossl111)]
+    pub fn SSL_set_post_handshake_auth(ssl: *mut SSL, val: c_int);
+    #[cfg(ossl111)]
+    pub fn SSL_verify_client_post_handshake(ssl: *mut SSL) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_key_update(ssl: *mut SSL, updatetype: c_int) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_get_key_update_type(ssl: *mut SSL) -> c_int;
+
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_psk_use_session_callback(
+        ctx: *mut SSL_CTX,
+        cb: SSL_psk_use_session_cb_func,
+    );
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_psk_find_session_callback(
+        ctx: *mut SSL_CTX,
+        cb: SSL_psk_find_session_cb_func,
+    );
+    #[cfg(ossl111)]
+    pub fn SSL_CIPHER_find(ssl: *mut SSL, ptr: *const c_uchar) -> *const SSL_CIPHER;
+
     pub fn SSL_free(ssl: *mut SSL);
     pub fn SSL_accept(ssl: *mut SSL) -> c_int;
     #[cfg(ossl111)]
@@ -1022,6 +1364,8 @@ extern "C" {
         num: size_t,
         written: *mut size_t,
     ) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_get_early_data_status(s: *const SSL) -> c_int;
     pub fn SSL_ctrl(ssl: *mut SSL, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
     pub fn SSL_CTX_ctrl(ctx: *mut SSL_CTX, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
     pub fn SSL_CTX_callback_ctrl(
@@ -1067,6 +1411,7 @@ extern "C" {
     pub fn SSL_shutdown(ssl: *mut SSL) -> c_int;
 
     pub fn SSL_CTX_set_client_CA_list(ctx: *mut SSL_CTX, list: *mut stack_st_X509_NAME);
+    pub fn SSL_set_client_CA_list(ssl: *mut SSL, list: *mut stack_st_X509_NAME);
 
     pub fn SSL_CTX_set_default_verify_paths(ctx: *mut SSL_CTX) -> c_int;
     pub fn SSL_CTX_load_verify_locations(
@@ -1197,6 +1542,68 @@ pub unsafe fn SSL_CTX_set_read_ahead(ctx: *mut SSL_CTX, m: c_long) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_SET_READ_AHEAD, m, ptr::null_mut())
 }
 
+pub unsafe fn SSL_CTX_sess_set_cache_size(ctx: *mut SSL_CTX, t: c_long) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SET_SESS_CACHE_SIZE, t, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_get_cache_size(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_GET_SESS_CACHE_SIZE, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_number(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_NUMBER, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect_good(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT_GOOD, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect_renegotiate(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT_RENEGOTIATE, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept_good(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT_GOOD, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept_renegotiate(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT_RENEGOTIATE, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_hits(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_HIT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_cb_hits(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CB_HIT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_misses(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_MISSES, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_timeouts(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_TIMEOUTS, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_cache_full(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CACHE_FULL, 0, ptr::null_mut())
+}
+
+extern "C" {
+    pub fn SSL_CTX_set_timeout(ctx: *mut SSL_CTX, t: c_long) -> c_long;
+    pub fn SSL_CTX_get_timeout(ctx: *const SSL_CTX) -> c_long;
+    pub fn SSL_CTX_flush_sessions(ctx: *mut SSL_CTX, tm: c_long);
+}
+
 extern "C" {
     // FIXME should take an option
     pub fn SSL_CTX_set_tmp_dh_callback(
@@ -2,4 +2,18 @@ use libc::*;
 
 pub const SSL3_VERSION: c_int = 0x300;
 
+pub const SSL3_RT_CHANGE_CIPHER_SPEC: c_int = 20;
+pub const SSL3_RT_ALERT: c_int = 21;
+pub const SSL3_RT_HANDSHAKE: c_int = 22;
+pub const SSL3_RT_APPLICATION_DATA: c_int = 23;
+#[cfg(ossl110)]
+pub const SSL3_RT_HEADER: c_int = 0x100;
+#[cfg(ossl111)]
+pub const SSL3_RT_INNER_CONTENT_TYPE: c_int = 0x101;
+
+pub const SSL3_AL_WARNING: c_int = 1;
+pub const SSL3_AL_FATAL: c_int = 2;
+
+pub const SSL3_AD_CLOSE_NOTIFY: c_int = 0;
+pub const SSL3_AD_HANDSHAKE_FAILURE: c_int = 40;
 pub const SSL3_AD_ILLEGAL_PARAMETER: c_int = 47;
@@ -109,3 +109,19 @@ pub unsafe fn SSL_CTX_set_tlsext_status_cb(
 pub unsafe fn SSL_CTX_set_tlsext_status_arg(ctx: *mut SSL_CTX, arg: *mut c_void) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_SET_TLSEXT_STATUS_REQ_CB_ARG, 0, arg)
 }
+
+pub unsafe fn SSL_CTX_set_tlsext_ticket_key_cb(
+    ctx: *mut SSL_CTX,
+    cb: Option<
+        unsafe extern "C" fn(
+            *mut SSL,
+            *mut c_uchar,
+            *mut c_uchar,
+            *mut EVP_CIPHER_CTX,
+            *mut HMAC_CTX,
+            c_int,
+        ) -> c_int,
+    >,
+) -> c_long {
+    SSL_CTX_callback_ctrl(ctx, SSL_CTRL_SET_TLSEXT_TICKET_KEY_CB, mem::transmute(cb))
+}
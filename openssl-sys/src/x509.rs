@@ -20,6 +20,8 @@ pub enum X509_EXTENSION {}
 
 stack!(stack_st_X509_EXTENSION);
 
+pub enum X509_ATTRIBUTE {}
+
 stack!(stack_st_X509_ATTRIBUTE);
 
 cfg_if! {
@@ -44,8 +46,8 @@ cfg_if! {
         #[repr(C)]
         pub struct X509_REQ {
             pub req_info: *mut X509_REQ_INFO,
-            sig_alg: *mut c_void,
-            signature: *mut c_void,
+            pub sig_alg: *mut X509_ALGOR,
+            pub signature: *mut ASN1_BIT_STRING,
             references: c_int,
         }
     }
@@ -74,6 +76,31 @@ cfg_if! {
 
 stack!(stack_st_X509);
 
+#[repr(C)]
+pub struct NETSCAPE_SPKAC {
+    pub pubkey: *mut X509_PUBKEY,
+    pub challenge: *mut ASN1_STRING,
+}
+
+cfg_if! {
+    if #[cfg(ossl110)] {
+        #[repr(C)]
+        pub struct NETSCAPE_SPKI {
+            pub spkac: *mut NETSCAPE_SPKAC,
+            sig_algor_algorithm: *mut ASN1_OBJECT,
+            sig_algor_parameter: *mut c_void,
+            pub signature: *mut ASN1_BIT_STRING,
+        }
+    } else {
+        #[repr(C)]
+        pub struct NETSCAPE_SPKI {
+            pub spkac: *mut NETSCAPE_SPKAC,
+            pub sig_algor: *mut X509_ALGOR,
+            pub signature: *mut ASN1_BIT_STRING,
+        }
+    }
+}
+
 extern "C" {
     pub fn X509_verify_cert_error_string(n: c_long) -> *const c_char;
 
@@ -240,6 +267,41 @@ extern "C" {
     pub fn X509_REQ_get_extensions(req: *mut X509_REQ) -> *mut stack_st_X509_EXTENSION;
     pub fn X509_REQ_add_extensions(req: *mut X509_REQ, exts: *mut stack_st_X509_EXTENSION)
         -> c_int;
+    pub fn X509_REQ_verify(req: *mut X509_REQ, pkey: *mut EVP_PKEY) -> c_int;
+    #[cfg(ossl110)]
+    pub fn X509_REQ_get0_signature(
+        req: *const X509_REQ,
+        psig: *mut *const ASN1_BIT_STRING,
+        palg: *mut *const X509_ALGOR,
+    );
+
+    pub fn X509_REQ_get_attr_count(req: *const X509_REQ) -> c_int;
+    pub fn X509_REQ_get_attr_by_NID(req: *const X509_REQ, nid: c_int, lastpos: c_int) -> c_int;
+    pub fn X509_REQ_get_attr(req: *const X509_REQ, loc: c_int) -> *mut X509_ATTRIBUTE;
+    pub fn X509_REQ_add1_attr_by_NID(
+        req: *mut X509_REQ,
+        nid: c_int,
+        type_: c_int,
+        bytes: *const c_uchar,
+        len: c_int,
+    ) -> c_int;
+    pub fn X509_REQ_add1_attr_by_txt(
+        req: *mut X509_REQ,
+        attrname: *const c_char,
+        type_: c_int,
+        bytes: *const c_uchar,
+        len: c_int,
+    ) -> c_int;
+
+    pub fn X509_ATTRIBUTE_free(attr: *mut X509_ATTRIBUTE);
+    pub fn X509_ATTRIBUTE_get0_object(attr: *mut X509_ATTRIBUTE) -> *mut ASN1_OBJECT;
+    pub fn X509_ATTRIBUTE_get0_type(attr: *mut X509_ATTRIBUTE, idx: c_int) -> *mut ASN1_TYPE;
+    pub fn X509_ATTRIBUTE_get0_data(
+        attr: *mut X509_ATTRIBUTE,
+        idx: c_int,
+        atrtype: c_int,
+        data: *mut c_void,
+    ) -> *mut c_void;
     pub fn X509_set_pubkey(x: *mut X509, pkey: *mut EVP_PKEY) -> c_int;
     #[cfg(any(ossl110, libressl273))]
     pub fn X509_getm_notBefore(x: *const X509) -> *mut ASN1_TIME;
@@ -343,6 +405,71 @@ cfg_if! {
     }
 }
 
+cfg_if! {
+    if #[cfg(ossl110)] {
+        extern "C" {
+            pub fn X509_ATTRIBUTE_count(attr: *const X509_ATTRIBUTE) -> c_int;
+        }
+    } else {
+        extern "C" {
+            pub fn X509_ATTRIBUTE_count(attr: *mut X509_ATTRIBUTE) -> c_int;
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(any(ossl110, libressl280))] {
+        extern "C" {
+            pub fn X509_get_ext_count(x: *const X509) -> c_int;
+            pub fn X509_get_ext(x: *const X509, loc: c_int) -> *mut X509_EXTENSION;
+            pub fn X509_get_ext_by_NID(x: *const X509, nid: c_int, lastpos: c_int) -> c_int;
+            pub fn X509_get_ext_by_OBJ(
+                x: *const X509,
+                obj: *const ASN1_OBJECT,
+                lastpos: c_int,
+            ) -> c_int;
+            pub fn X509_EXTENSION_get_object(ex: *mut X509_EXTENSION) -> *mut ASN1_OBJECT;
+        }
+    } else {
+        extern "C" {
+            pub fn X509_get_ext_count(x: *mut X509) -> c_int;
+            pub fn X509_get_ext(x: *mut X509, loc: c_int) -> *mut X509_EXTENSION;
+            pub fn X509_get_ext_by_NID(x: *mut X509, nid: c_int, lastpos: c_int) -> c_int;
+            pub fn X509_get_ext_by_OBJ(x: *mut X509, obj: *mut ASN1_OBJECT, lastpos: c_int)
+                -> c_int;
+            pub fn X509_EXTENSION_get_object(ex: *mut X509_EXTENSION) -> *mut ASN1_OBJECT;
+        }
+    }
+}
+
 extern "C" {
+    pub fn X509_dup(x: *mut X509) -> *mut X509;
+    pub fn X509_delete_ext(x: *mut X509, loc: c_int) -> *mut X509_EXTENSION;
+
     pub fn X509_verify_cert(ctx: *mut X509_STORE_CTX) -> c_int;
 }
+
+extern "C" {
+    pub fn X509_alias_set1(x: *mut X509, name: *const c_uchar, len: c_int) -> c_int;
+    pub fn X509_keyid_set1(x: *mut X509, id: *const c_uchar, len: c_int) -> c_int;
+    pub fn X509_alias_get0(x: *mut X509, len: *mut c_int) -> *mut c_uchar;
+    pub fn X509_keyid_get0(x: *mut X509, len: *mut c_int) -> *mut c_uchar;
+}
+
+extern "C" {
+    pub fn NETSCAPE_SPKI_new() -> *mut NETSCAPE_SPKI;
+    pub fn NETSCAPE_SPKI_free(a: *mut NETSCAPE_SPKI);
+    pub fn d2i_NETSCAPE_SPKI(
+        a: *mut *mut NETSCAPE_SPKI,
+        pp: *mut *const c_uchar,
+        length: c_long,
+    ) -> *mut NETSCAPE_SPKI;
+    pub fn i2d_NETSCAPE_SPKI(a: *mut NETSCAPE_SPKI, pp: *mut *mut c_uchar) -> c_int;
+    pub fn NETSCAPE_SPKI_b64_decode(str: *const c_char, len: c_int) -> *mut NETSCAPE_SPKI;
+    pub fn NETSCAPE_SPKI_b64_encode(x: *mut NETSCAPE_SPKI) -> *mut c_char;
+    pub fn NETSCAPE_SPKI_get_pubkey(x: *mut NETSCAPE_SPKI) -> *mut EVP_PKEY;
+    pub fn NETSCAPE_SPKI_set_pubkey(x: *mut NETSCAPE_SPKI, pkey: *mut EVP_PKEY) -> c_int;
+    pub fn NETSCAPE_SPKI_sign(x: *mut NETSCAPE_SPKI, pkey: *mut EVP_PKEY, md: *const EVP_MD)
+        -> c_int;
+    pub fn NETSCAPE_SPKI_verify(x: *mut NETSCAPE_SPKI, pkey: *mut EVP_PKEY) -> c_int;
+}
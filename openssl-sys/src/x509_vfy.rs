@@ -95,6 +95,42 @@ cfg_if! {
     }
 }
 
+pub const X509_TRUST_DEFAULT: c_int = 0;
+pub const X509_TRUST_COMPAT: c_int = 1;
+pub const X509_TRUST_SSL_CLIENT: c_int = 2;
+pub const X509_TRUST_SSL_SERVER: c_int = 3;
+pub const X509_TRUST_EMAIL: c_int = 4;
+pub const X509_TRUST_OBJECT_SIGN: c_int = 5;
+pub const X509_TRUST_OCSP_SIGN: c_int = 6;
+pub const X509_TRUST_OCSP_REQUEST: c_int = 7;
+pub const X509_TRUST_TSA: c_int = 8;
+
+pub const X509_TRUST_TRUSTED: c_int = 1;
+pub const X509_TRUST_REJECTED: c_int = 2;
+pub const X509_TRUST_UNTRUSTED: c_int = 3;
+
+pub const X509_PURPOSE_SSL_CLIENT: c_int = 1;
+pub const X509_PURPOSE_SSL_SERVER: c_int = 2;
+pub const X509_PURPOSE_NS_SSL_SERVER: c_int = 3;
+pub const X509_PURPOSE_SMIME_SIGN: c_int = 4;
+pub const X509_PURPOSE_SMIME_ENCRYPT: c_int = 5;
+pub const X509_PURPOSE_CRL_SIGN: c_int = 6;
+pub const X509_PURPOSE_ANY: c_int = 7;
+pub const X509_PURPOSE_OCSP_HELPER: c_int = 8;
+pub const X509_PURPOSE_TIMESTAMP_SIGN: c_int = 9;
+
+extern "C" {
+    pub fn X509_add1_trust_object(x: *mut X509, obj: *const ASN1_OBJECT) -> c_int;
+    pub fn X509_add1_reject_object(x: *mut X509, obj: *const ASN1_OBJECT) -> c_int;
+    pub fn X509_trust_clear(x: *mut X509);
+    pub fn X509_reject_clear(x: *mut X509);
+    #[cfg(ossl110)]
+    pub fn X509_get0_trust_objects(x: *mut X509) -> *mut stack_st_ASN1_OBJECT;
+    #[cfg(ossl110)]
+    pub fn X509_get0_reject_objects(x: *mut X509) -> *mut stack_st_ASN1_OBJECT;
+    pub fn X509_check_trust(x: *mut X509, id: c_int, flags: c_int) -> c_int;
+}
+
 extern "C" {
     pub fn X509_STORE_new() -> *mut X509_STORE;
     pub fn X509_STORE_free(store: *mut X509_STORE);
@@ -113,6 +149,8 @@ extern "C" {
     pub fn X509_STORE_add_cert(store: *mut X509_STORE, x: *mut X509) -> c_int;
 
     pub fn X509_STORE_set_default_paths(store: *mut X509_STORE) -> c_int;
+    pub fn X509_STORE_set_purpose(store: *mut X509_STORE, purpose: c_int) -> c_int;
+    pub fn X509_STORE_set_trust(store: *mut X509_STORE, trust: c_int) -> c_int;
 
     pub fn X509_STORE_CTX_get_ex_data(ctx: *mut X509_STORE_CTX, idx: c_int) -> *mut c_void;
     pub fn X509_STORE_CTX_get_error(ctx: *mut X509_STORE_CTX) -> c_int;
@@ -124,6 +162,7 @@ cfg_if! {
     if #[cfg(ossl110)] {
         extern "C" {
             pub fn X509_STORE_CTX_get0_chain(ctx: *mut X509_STORE_CTX) -> *mut stack_st_X509;
+            pub fn X509_STORE_CTX_get0_untrusted(ctx: *mut X509_STORE_CTX) -> *mut stack_st_X509;
         }
     } else {
         extern "C" {
@@ -143,6 +182,10 @@ extern "C" {
         namelen: size_t,
     ) -> c_int;
     #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_set_purpose(param: *mut X509_VERIFY_PARAM, purpose: c_int) -> c_int;
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_set_trust(param: *mut X509_VERIFY_PARAM, trust: c_int) -> c_int;
+    #[cfg(any(ossl102, libressl261))]
     pub fn X509_VERIFY_PARAM_set_hostflags(param: *mut X509_VERIFY_PARAM, flags: c_uint);
     #[cfg(any(ossl102, libressl261))]
     pub fn X509_VERIFY_PARAM_set1_ip(
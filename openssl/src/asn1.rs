@@ -36,6 +36,7 @@ use bio::MemBio;
 use bn::BigNum;
 use error::ErrorStack;
 use nid::Nid;
+use stack::Stackable;
 use string::OpensslString;
 use {cvt, cvt_p};
 
@@ -273,6 +274,24 @@ foreign_type_and_impl_send_sync! {
     pub struct Asn1ObjectRef;
 }
 
+impl Asn1Object {
+    /// Returns the object corresponding to a NID.
+    ///
+    /// This corresponds to [`OBJ_nid2obj`].
+    ///
+    /// [`OBJ_nid2obj`]: https://www.openssl.org/docs/man1.1.0/crypto/OBJ_nid2obj.html
+    pub fn from_nid(nid: Nid) -> Result<Asn1Object, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::OBJ_nid2obj(nid.as_raw())).map(Asn1Object)
+        }
+    }
+}
+
+impl Stackable for Asn1Object {
+    type StackType = ffi::stack_st_ASN1_OBJECT;
+}
+
 impl Asn1ObjectRef {
     /// Returns the NID associated with this OID.
     pub fn nid(&self) -> Nid {
@@ -205,6 +205,20 @@ impl EcGroupRef {
         unsafe { ffi::EC_GROUP_get_degree(self.as_ptr()) as u32 }
     }
 
+    /// Returns the name of the curve, if it is a named curve.
+    ///
+    /// OpenSSL documentation at [`EC_GROUP_get_curve_name`]
+    ///
+    /// [`EC_GROUP_get_curve_name`]: https://www.openssl.org/docs/man1.1.0/crypto/EC_GROUP_get_curve_name.html
+    pub fn curve_name(&self) -> Option<Nid> {
+        let nid = unsafe { ffi::EC_GROUP_get_curve_name(self.as_ptr()) };
+        if nid > 0 {
+            Some(Nid::from_raw(nid))
+        } else {
+            None
+        }
+    }
+
     /// Places the order of the curve in the provided `BigNum`.
     ///
     /// OpenSSL documentation at [`EC_GROUP_get_order`]
@@ -813,6 +827,12 @@ mod test {
         EcKey::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
     }
 
+    #[test]
+    fn curve_name() {
+        let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+        assert_eq!(group.curve_name(), Some(Nid::SECP384R1));
+    }
+
     #[test]
     fn generate() {
         let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
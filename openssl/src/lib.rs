@@ -119,6 +119,11 @@ extern crate foreign_types;
 extern crate lazy_static;
 extern crate libc;
 extern crate openssl_sys as ffi;
+#[cfg(feature = "futures-io")]
+extern crate futures_io;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
 
 #[cfg(test)]
 extern crate data_encoding;
@@ -1047,6 +1047,10 @@ impl Nid {
     pub const AES_128_CBC_HMAC_SHA1: Nid = Nid(ffi::NID_aes_128_cbc_hmac_sha1);
     pub const AES_192_CBC_HMAC_SHA1: Nid = Nid(ffi::NID_aes_192_cbc_hmac_sha1);
     pub const AES_256_CBC_HMAC_SHA1: Nid = Nid(ffi::NID_aes_256_cbc_hmac_sha1);
+    #[cfg(ossl110)]
+    pub const X25519: Nid = Nid(ffi::NID_X25519);
+    #[cfg(ossl111)]
+    pub const X448: Nid = Nid(ffi::NID_X448);
 }
 
 #[cfg(test)]
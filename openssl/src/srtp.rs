@@ -3,6 +3,7 @@ use foreign_types::ForeignTypeRef;
 use libc::c_ulong;
 use stack::Stackable;
 use std::ffi::CStr;
+use std::fmt;
 use std::str;
 
 /// fake free method, since SRTP_PROTECTION_PROFILE is static
@@ -54,4 +55,151 @@ impl SrtpProfileId {
     pub const SRTP_AES128_F8_SHA1_32: SrtpProfileId = SrtpProfileId(ffi::SRTP_AES128_F8_SHA1_32);
     pub const SRTP_NULL_SHA1_80: SrtpProfileId = SrtpProfileId(ffi::SRTP_NULL_SHA1_80);
     pub const SRTP_NULL_SHA1_32: SrtpProfileId = SrtpProfileId(ffi::SRTP_NULL_SHA1_32);
+    pub const SRTP_AEAD_AES_128_GCM: SrtpProfileId = SrtpProfileId(ffi::SRTP_AEAD_AES_128_GCM);
+    pub const SRTP_AEAD_AES_256_GCM: SrtpProfileId = SrtpProfileId(ffi::SRTP_AEAD_AES_256_GCM);
+
+    /// Returns the length in bytes of the SRTP master key used by the profile.
+    ///
+    /// Returns `None` for unknown profiles.
+    pub fn master_key_len(&self) -> Option<usize> {
+        match *self {
+            SrtpProfileId::SRTP_AES128_CM_SHA1_80
+            | SrtpProfileId::SRTP_AES128_CM_SHA1_32
+            | SrtpProfileId::SRTP_AES128_F8_SHA1_80
+            | SrtpProfileId::SRTP_AES128_F8_SHA1_32
+            | SrtpProfileId::SRTP_NULL_SHA1_80
+            | SrtpProfileId::SRTP_NULL_SHA1_32
+            | SrtpProfileId::SRTP_AEAD_AES_128_GCM => Some(16),
+            SrtpProfileId::SRTP_AEAD_AES_256_GCM => Some(32),
+            _ => None,
+        }
+    }
+
+    /// Returns the length in bytes of the SRTP master salt used by the profile.
+    ///
+    /// Returns `None` for unknown profiles.
+    pub fn master_salt_len(&self) -> Option<usize> {
+        match *self {
+            SrtpProfileId::SRTP_AES128_CM_SHA1_80
+            | SrtpProfileId::SRTP_AES128_CM_SHA1_32
+            | SrtpProfileId::SRTP_AES128_F8_SHA1_80
+            | SrtpProfileId::SRTP_AES128_F8_SHA1_32
+            | SrtpProfileId::SRTP_NULL_SHA1_80
+            | SrtpProfileId::SRTP_NULL_SHA1_32 => Some(14),
+            SrtpProfileId::SRTP_AEAD_AES_128_GCM | SrtpProfileId::SRTP_AEAD_AES_256_GCM => Some(12),
+            _ => None,
+        }
+    }
+}
+
+/// SRTP master keys and salts derived from a DTLS handshake as described in [RFC 5764].
+///
+/// The keying material is exported with the label `EXTRACTOR-dtls_srtp` and split into the
+/// client's and server's master keys and salts. The local keys protect outgoing SRTP packets and
+/// the remote keys unprotect incoming ones.
+///
+/// [RFC 5764]: https://tools.ietf.org/html/rfc5764#section-4.2
+#[derive(Clone)]
+pub struct SrtpKeyingMaterial {
+    profile: SrtpProfileId,
+    material: Vec<u8>,
+    key_len: usize,
+    salt_len: usize,
+    is_server: bool,
+}
+
+impl SrtpKeyingMaterial {
+    pub(crate) fn new(
+        profile: SrtpProfileId,
+        material: Vec<u8>,
+        key_len: usize,
+        salt_len: usize,
+        is_server: bool,
+    ) -> SrtpKeyingMaterial {
+        debug_assert_eq!(material.len(), 2 * (key_len + salt_len));
+        SrtpKeyingMaterial {
+            profile,
+            material,
+            key_len,
+            salt_len,
+            is_server,
+        }
+    }
+
+    /// Returns the negotiated protection profile.
+    pub fn profile(&self) -> SrtpProfileId {
+        self.profile
+    }
+
+    /// Returns the client's master key.
+    pub fn client_master_key(&self) -> &[u8] {
+        &self.material[..self.key_len]
+    }
+
+    /// Returns the server's master key.
+    pub fn server_master_key(&self) -> &[u8] {
+        &self.material[self.key_len..2 * self.key_len]
+    }
+
+    /// Returns the client's master salt.
+    pub fn client_master_salt(&self) -> &[u8] {
+        let start = 2 * self.key_len;
+        &self.material[start..start + self.salt_len]
+    }
+
+    /// Returns the server's master salt.
+    pub fn server_master_salt(&self) -> &[u8] {
+        let start = 2 * self.key_len + self.salt_len;
+        &self.material[start..start + self.salt_len]
+    }
+
+    /// Returns `true` if the keying material was derived by the server side of the connection.
+    pub fn is_server(&self) -> bool {
+        self.is_server
+    }
+
+    /// Returns the master key used to protect packets sent by this side of the connection.
+    pub fn local_master_key(&self) -> &[u8] {
+        if self.is_server {
+            self.server_master_key()
+        } else {
+            self.client_master_key()
+        }
+    }
+
+    /// Returns the master salt used to protect packets sent by this side of the connection.
+    pub fn local_master_salt(&self) -> &[u8] {
+        if self.is_server {
+            self.server_master_salt()
+        } else {
+            self.client_master_salt()
+        }
+    }
+
+    /// Returns the master key used to unprotect packets received from the peer.
+    pub fn remote_master_key(&self) -> &[u8] {
+        if self.is_server {
+            self.client_master_key()
+        } else {
+            self.server_master_key()
+        }
+    }
+
+    /// Returns the master salt used to unprotect packets received from the peer.
+    pub fn remote_master_salt(&self) -> &[u8] {
+        if self.is_server {
+            self.client_master_salt()
+        } else {
+            self.server_master_salt()
+        }
+    }
+}
+
+impl fmt::Debug for SrtpKeyingMaterial {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("SrtpKeyingMaterial")
+            .field("profile", &self.profile)
+            .field("is_server", &self.is_server)
+            .finish()
+    }
 }
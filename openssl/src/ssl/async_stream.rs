@@ -0,0 +1,335 @@
+use ffi;
+use foreign_types::ForeignTypeRef;
+use futures_io::{AsyncRead, AsyncWrite};
+use std::fmt;
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::pin::Pin;
+use std::ptr;
+use std::task::{Context, Poll};
+
+use error::ErrorStack;
+use ssl::handshake_info::timed_handshake;
+use ssl::{Error, ErrorCode, Ssl, SslRef, SslStream};
+
+/// Adapts an `AsyncRead + AsyncWrite` stream to `Read + Write` for use by the stream BIO.
+///
+/// The task context is installed for the duration of each OpenSSL call, and `Poll::Pending` is
+/// reported to OpenSSL as a `WouldBlock` error, so the waker is registered by whichever read or
+/// write the BIO was performing when it had to stop.
+struct StreamWrapper<S> {
+    stream: S,
+    context: *mut (),
+}
+
+impl<S> StreamWrapper<S>
+where
+    S: Unpin,
+{
+    fn with_context<F, R>(&mut self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(Pin<&mut S>, &mut Context) -> Poll<io::Result<R>>,
+    {
+        assert!(!self.context.is_null());
+        unsafe {
+            let cx = &mut *(self.context as *mut Context);
+            match f(Pin::new(&mut self.stream), cx) {
+                Poll::Ready(r) => r,
+                Poll::Pending => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+            }
+        }
+    }
+}
+
+impl<S> Read for StreamWrapper<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.with_context(|s, cx| s.poll_read(cx, buf))
+    }
+}
+
+impl<S> Write for StreamWrapper<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.with_context(|s, cx| s.poll_write(cx, buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.with_context(|s, cx| s.poll_flush(cx))
+    }
+}
+
+// the raw context pointer is only set while the stream is borrowed mutably
+unsafe impl<S: Send> Send for StreamWrapper<S> {}
+unsafe impl<S: Sync> Sync for StreamWrapper<S> {}
+
+fn cvt_io<T>(r: io::Result<T>) -> Poll<io::Result<T>> {
+    match r {
+        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => Poll::Pending,
+        r => Poll::Ready(r),
+    }
+}
+
+fn cvt_ssl<T>(r: Result<T, Error>) -> Poll<Result<T, Error>> {
+    match r {
+        Err(ref e)
+            if (e.code() == ErrorCode::WANT_READ || e.code() == ErrorCode::WANT_WRITE)
+                && e.io_error().map(|e| e.kind()) == Some(io::ErrorKind::WouldBlock) =>
+        {
+            Poll::Pending
+        }
+        r => Poll::Ready(r),
+    }
+}
+
+/// An asynchronous TLS stream over an `AsyncRead + AsyncWrite` transport.
+///
+/// The stream implements the `futures-io` `AsyncRead` and `AsyncWrite` traits. It is only
+/// available with the `futures-io` Cargo feature, which requires Rust 1.36 or newer.
+///
+/// # Examples
+///
+/// ```no_run
+/// # extern crate openssl;
+/// # extern crate futures_io;
+/// # use futures_io::{AsyncRead, AsyncWrite};
+/// use openssl::ssl::{AsyncSslStream, SslConnector, SslMethod};
+///
+/// # fn example<S: AsyncRead + AsyncWrite + Unpin>(stream: S) {
+/// let connector = SslConnector::builder(SslMethod::tls()).unwrap().build();
+/// let ssl = connector
+///     .configure()
+///     .unwrap()
+///     .into_ssl("google.com")
+///     .unwrap();
+/// let mut stream = AsyncSslStream::new(ssl, stream).unwrap();
+/// let handshake = stream.connect();
+/// // await `handshake` in an async context
+/// # drop(handshake);
+/// # }
+/// # fn main() {}
+/// ```
+pub struct AsyncSslStream<S> {
+    stream: SslStream<StreamWrapper<S>>,
+    shutdown_sent: bool,
+}
+
+impl<S> AsyncSslStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Creates a new `AsyncSslStream`.
+    ///
+    /// The handshake must be performed with `connect`, `accept`, or `do_handshake` before data is
+    /// transferred.
+    pub fn new(ssl: Ssl, stream: S) -> Result<AsyncSslStream<S>, ErrorStack> {
+        let stream = StreamWrapper {
+            stream,
+            context: ptr::null_mut(),
+        };
+        Ok(AsyncSslStream {
+            stream: SslStream::new_base(ssl, stream),
+            shutdown_sent: false,
+        })
+    }
+
+    /// Returns a future which performs the client side of the handshake.
+    ///
+    /// This corresponds to [`SSL_connect`].
+    ///
+    /// [`SSL_connect`]: https://www.openssl.org/docs/manmaster/man3/SSL_connect.html
+    pub fn connect<'a>(&'a mut self) -> HandshakeFuture<'a, S> {
+        unsafe { ffi::SSL_set_connect_state(self.stream.ssl().as_ptr()) }
+        HandshakeFuture(self)
+    }
+
+    /// Returns a future which performs the server side of the handshake.
+    ///
+    /// This corresponds to [`SSL_accept`].
+    ///
+    /// [`SSL_accept`]: https://www.openssl.org/docs/manmaster/man3/SSL_accept.html
+    pub fn accept<'a>(&'a mut self) -> HandshakeFuture<'a, S> {
+        unsafe { ffi::SSL_set_accept_state(self.stream.ssl().as_ptr()) }
+        HandshakeFuture(self)
+    }
+
+    /// Returns a future which performs the handshake in the mode previously configured on the
+    /// `Ssl`.
+    ///
+    /// This corresponds to [`SSL_do_handshake`].
+    ///
+    /// [`SSL_do_handshake`]: https://www.openssl.org/docs/manmaster/man3/SSL_do_handshake.html
+    pub fn do_handshake<'a>(&'a mut self) -> HandshakeFuture<'a, S> {
+        HandshakeFuture(self)
+    }
+
+    /// Returns a future which shuts down the session.
+    ///
+    /// The future resolves once the close notify message has been sent; it does not wait for the
+    /// peer's. The underlying stream is not closed.
+    ///
+    /// This corresponds to [`SSL_shutdown`].
+    ///
+    /// [`SSL_shutdown`]: https://www.openssl.org/docs/manmaster/man3/SSL_shutdown.html
+    pub fn shutdown<'a>(&'a mut self) -> ShutdownFuture<'a, S> {
+        ShutdownFuture(self)
+    }
+
+    /// Attempts to perform the handshake.
+    pub fn poll_do_handshake(&mut self, cx: &mut Context) -> Poll<Result<(), Error>> {
+        self.with_context(cx, |s| {
+            let ret = unsafe { timed_handshake(s.ssl().as_ptr(), ffi::SSL_do_handshake) };
+            if ret > 0 {
+                Ok(())
+            } else {
+                Err(s.make_error(ret))
+            }
+        })
+    }
+
+    /// Attempts to send a close notify message to the peer.
+    ///
+    /// Once the message has been sent, this returns `Ready` immediately.
+    pub fn poll_shutdown(&mut self, cx: &mut Context) -> Poll<Result<(), Error>> {
+        // calling SSL_shutdown again would wait for the peer's close notify
+        if self.shutdown_sent {
+            return Poll::Ready(Ok(()));
+        }
+
+        let r = self.with_context(cx, |s| match s.shutdown() {
+            Ok(_) => Ok(()),
+            Err(ref e) if e.code() == ErrorCode::ZERO_RETURN => Ok(()),
+            Err(e) => Err(e),
+        });
+        if let Poll::Ready(Ok(())) = r {
+            self.shutdown_sent = true;
+        }
+        r
+    }
+
+    fn with_context<F, T>(&mut self, cx: &mut Context, f: F) -> Poll<Result<T, Error>>
+    where
+        F: FnOnce(&mut SslStream<StreamWrapper<S>>) -> Result<T, Error>,
+    {
+        self.stream.get_mut().context = cx as *mut Context as *mut ();
+        let r = f(&mut self.stream);
+        self.stream.get_mut().context = ptr::null_mut();
+        cvt_ssl(r)
+    }
+
+    fn with_context_io<F, T>(&mut self, cx: &mut Context, f: F) -> Poll<io::Result<T>>
+    where
+        F: FnOnce(&mut SslStream<StreamWrapper<S>>) -> io::Result<T>,
+    {
+        self.stream.get_mut().context = cx as *mut Context as *mut ();
+        let r = f(&mut self.stream);
+        self.stream.get_mut().context = ptr::null_mut();
+        cvt_io(r)
+    }
+}
+
+impl<S> AsyncSslStream<S> {
+    /// Returns a shared reference to the `Ssl` object associated with this stream.
+    pub fn ssl(&self) -> &SslRef {
+        self.stream.ssl()
+    }
+
+    /// Returns a shared reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream.get_ref().stream
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    ///
+    /// # Warning
+    ///
+    /// It is inadvisable to read from or write to the underlying stream as it
+    /// will most likely corrupt the SSL session.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream.get_mut().stream
+    }
+}
+
+impl<S> fmt::Debug for AsyncSslStream<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("AsyncSslStream")
+            .field("stream", self.get_ref())
+            .field("ssl", &self.ssl())
+            .finish()
+    }
+}
+
+impl<S> AsyncRead for AsyncSslStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().with_context_io(cx, |s| s.read(buf))
+    }
+}
+
+impl<S> AsyncWrite for AsyncSslStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.get_mut().with_context_io(cx, |s| s.write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        self.get_mut().with_context_io(cx, |s| s.flush())
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        match this.poll_shutdown(cx) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(e)) => {
+                return Poll::Ready(Err(e
+                    .into_io_error()
+                    .unwrap_or_else(|e| io::Error::new(io::ErrorKind::Other, e))))
+            }
+            Poll::Pending => return Poll::Pending,
+        }
+        Pin::new(this.get_mut()).poll_close(cx)
+    }
+}
+
+/// A future performing a TLS handshake on an `AsyncSslStream`.
+pub struct HandshakeFuture<'a, S: 'a>(&'a mut AsyncSslStream<S>);
+
+impl<'a, S> Future for HandshakeFuture<'a, S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    type Output = Result<(), Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        self.get_mut().0.poll_do_handshake(cx)
+    }
+}
+
+/// A future shutting down the TLS session of an `AsyncSslStream`.
+pub struct ShutdownFuture<'a, S: 'a>(&'a mut AsyncSslStream<S>);
+
+impl<'a, S> Future for ShutdownFuture<'a, S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    type Output = Result<(), Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        self.get_mut().0.poll_shutdown(cx)
+    }
+}
@@ -3,9 +3,7 @@ use foreign_types::ForeignType;
 use foreign_types::ForeignTypeRef;
 #[cfg(any(ossl111, not(osslconf = "OPENSSL_NO_PSK")))]
 use libc::c_char;
-#[cfg(ossl111)]
-use libc::size_t;
-use libc::{c_int, c_uchar, c_uint, c_void};
+use libc::{c_int, c_uchar, c_uint, c_void, size_t};
 #[cfg(any(ossl111, not(osslconf = "OPENSSL_NO_PSK")))]
 use std::ffi::CStr;
 use std::mem;
@@ -19,15 +17,24 @@ use dh::Dh;
 #[cfg(all(ossl101, not(ossl110)))]
 use ec::EcKey;
 use error::ErrorStack;
-use pkey::Params;
+#[cfg(ossl111)]
+use hash::MessageDigest;
+use pkey::{PKey, Params, Private};
+use rand::rand_bytes;
 #[cfg(any(ossl102, libressl261))]
 use ssl::AlpnError;
+#[cfg(ossl102)]
+use ssl::CertCallbackResponse;
 #[cfg(ossl111)]
 use ssl::{ExtensionContext, ClientHelloResponse};
-use ssl::{SniError, Ssl, SslAlert, SslContext, SslContextRef, SslRef, SslSession, SslSessionRef};
+use ssl::{
+    MessageDirection, SniError, Ssl, SslAlert, SslContentType, SslContext, SslContextRef,
+    SslInfoEvent, SslMessage, SslRef, SslSession, SslSessionRef, TicketKeyRequest,
+    TicketKeyResponse,
+};
 #[cfg(ossl111)]
 use x509::X509Ref;
-use x509::{X509StoreContext, X509StoreContextRef};
+use x509::{X509, X509StoreContext, X509StoreContextRef};
 
 pub extern "C" fn raw_verify<F>(preverify_ok: c_int, x509_ctx: *mut ffi::X509_STORE_CTX) -> c_int
 where
@@ -344,6 +351,74 @@ where
     }
 }
 
+pub unsafe extern "C" fn raw_ticket_key<F>(
+    ssl: *mut ffi::SSL,
+    key_name: *mut c_uchar,
+    iv: *mut c_uchar,
+    cipher_ctx: *mut ffi::EVP_CIPHER_CTX,
+    hmac_ctx: *mut ffi::HMAC_CTX,
+    enc: c_int,
+) -> c_int
+where
+    F: Fn(&mut SslRef, TicketKeyRequest) -> Result<TicketKeyResponse, ErrorStack>
+        + 'static
+        + Sync
+        + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: ticket key callback missing") as *const F;
+    let key_name = slice::from_raw_parts_mut(key_name, 16);
+    let iv = slice::from_raw_parts_mut(iv, 16);
+
+    let request = if enc == 1 {
+        if let Err(e) = rand_bytes(iv) {
+            e.put();
+            return -1;
+        }
+        TicketKeyRequest::Encrypt
+    } else {
+        TicketKeyRequest::Decrypt(key_name)
+    };
+
+    let (key, ret) = match (*callback)(ssl, request) {
+        Ok(TicketKeyResponse::Key(key)) => (key, 1),
+        Ok(TicketKeyResponse::Renew(key)) => (key, if enc == 1 { 1 } else { 2 }),
+        Ok(TicketKeyResponse::None) => return 0,
+        Err(e) => {
+            e.put();
+            return -1;
+        }
+    };
+
+    if enc == 1 {
+        key_name.copy_from_slice(key.name());
+    }
+
+    if ffi::EVP_CipherInit_ex(
+        cipher_ctx,
+        ffi::EVP_aes_256_cbc(),
+        ptr::null_mut(),
+        key.aes_key().as_ptr(),
+        iv.as_ptr(),
+        enc,
+    ) <= 0
+        || ffi::HMAC_Init_ex(
+            hmac_ctx,
+            key.hmac_key().as_ptr() as *const c_void,
+            key.hmac_key().len() as c_int,
+            ffi::EVP_sha256(),
+            ptr::null_mut(),
+        ) <= 0
+    {
+        return -1;
+    }
+
+    ret
+}
+
 pub unsafe extern "C" fn raw_new_session<F>(
     ssl: *mut ffi::SSL,
     session: *mut ffi::SSL_SESSION,
@@ -682,3 +757,191 @@ where
         }
     }
 }
+
+#[cfg(ossl111)]
+struct PskSessionIdentity(Vec<u8>);
+
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_psk_use_session<F>(
+    ssl: *mut ffi::SSL,
+    md: *const ffi::EVP_MD,
+    id: *mut *const c_uchar,
+    idlen: *mut size_t,
+    sess: *mut *mut ffi::SSL_SESSION,
+) -> c_int
+where
+    F: Fn(&mut SslRef, Option<MessageDigest>) -> Result<Option<(Vec<u8>, SslSession)>, ErrorStack>
+        + 'static
+        + Sync
+        + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: psk use session callback missing") as *const F;
+    let md = if md.is_null() {
+        None
+    } else {
+        Some(MessageDigest::from_ptr(md))
+    };
+
+    match (*callback)(ssl, md) {
+        Ok(Some((identity, session))) => {
+            // OpenSSL copies the identity once we return, but it needs to outlive this call.
+            let idx = Ssl::cached_ex_index::<PskSessionIdentity>();
+            if ssl.ex_data(idx).is_some() {
+                ssl.ex_data_mut(idx).unwrap().0 = identity;
+            } else {
+                ssl.set_ex_data(idx, PskSessionIdentity(identity));
+            }
+            let identity = &ssl.ex_data(idx).unwrap().0;
+            *id = identity.as_ptr();
+            *idlen = identity.len();
+            *sess = session.as_ptr();
+            mem::forget(session);
+            1
+        }
+        Ok(None) => {
+            *id = ptr::null();
+            *idlen = 0;
+            *sess = ptr::null_mut();
+            1
+        }
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_psk_find_session<F>(
+    ssl: *mut ffi::SSL,
+    identity: *const c_uchar,
+    identity_len: size_t,
+    sess: *mut *mut ffi::SSL_SESSION,
+) -> c_int
+where
+    F: Fn(&mut SslRef, &[u8]) -> Result<Option<SslSession>, ErrorStack> + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: psk find session callback missing") as *const F;
+    let identity = if identity_len == 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(identity, identity_len)
+    };
+
+    match (*callback)(ssl, identity) {
+        Ok(session) => {
+            *sess = match session {
+                Some(session) => {
+                    let ptr = session.as_ptr();
+                    mem::forget(session);
+                    ptr
+                }
+                None => ptr::null_mut(),
+            };
+            1
+        }
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(ossl102)]
+pub unsafe extern "C" fn raw_cert<F>(ssl: *mut ffi::SSL, arg: *mut c_void) -> c_int
+where
+    F: Fn(&mut SslRef) -> Result<CertCallbackResponse, ErrorStack> + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = arg as *const F;
+
+    match (*callback)(ssl) {
+        Ok(r) => r.0,
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+pub unsafe extern "C" fn raw_client_cert<F>(
+    ssl: *mut ffi::SSL,
+    x509: *mut *mut ffi::X509,
+    pkey: *mut *mut ffi::EVP_PKEY,
+) -> c_int
+where
+    F: Fn(&mut SslRef) -> Option<(X509, PKey<Private>)> + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: client cert callback missing") as *const F;
+
+    match (*callback)(ssl) {
+        Some((cert, key)) => {
+            // OpenSSL takes ownership of the certificate and key
+            *x509 = cert.as_ptr();
+            *pkey = key.as_ptr();
+            mem::forget(cert);
+            mem::forget(key);
+            1
+        }
+        None => 0,
+    }
+}
+
+pub unsafe extern "C" fn raw_msg<F>(
+    write_p: c_int,
+    version: c_int,
+    content_type: c_int,
+    buf: *const c_void,
+    len: size_t,
+    ssl: *mut ffi::SSL,
+    arg: *mut c_void,
+) where
+    F: Fn(&SslRef, SslMessage) + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr(ssl);
+    let callback = arg as *const F;
+    let data = if len == 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(buf as *const u8, len)
+    };
+    let message = SslMessage {
+        direction: if write_p == 0 {
+            MessageDirection::Received
+        } else {
+            MessageDirection::Sent
+        },
+        version,
+        content_type: SslContentType::from_raw(content_type),
+        data,
+    };
+
+    (*callback)(ssl, message);
+}
+
+pub unsafe extern "C" fn raw_info<F>(ssl: *const ffi::SSL, where_: c_int, ret: c_int)
+where
+    F: Fn(&SslRef, SslInfoEvent) + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr(ssl as *mut _);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: info callback missing");
+
+    if let Some(event) = SslInfoEvent::from_raw(where_, ret) {
+        callback(ssl, event);
+    }
+}
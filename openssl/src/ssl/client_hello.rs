@@ -0,0 +1,512 @@
+use libc::c_int;
+use std::error::Error;
+use std::fmt;
+use std::str;
+
+use error::ErrorStack;
+use hash::{hash, MessageDigest};
+use ssl::SslVersion;
+
+const EXT_SERVER_NAME: u16 = 0;
+pub(crate) const EXT_SUPPORTED_GROUPS: u16 = 10;
+pub(crate) const EXT_EC_POINT_FORMATS: u16 = 11;
+pub(crate) const EXT_SIGNATURE_ALGORITHMS: u16 = 13;
+const EXT_ALPN: u16 = 16;
+pub(crate) const EXT_SUPPORTED_VERSIONS: u16 = 43;
+
+const RECORD_HANDSHAKE: u8 = 22;
+const HANDSHAKE_CLIENT_HELLO: u8 = 1;
+const HANDSHAKE_SERVER_HELLO: u8 = 2;
+const NAME_TYPE_HOST_NAME: u8 = 0;
+const MAX_FRAGMENT_LEN: usize = 1 << 14;
+
+macro_rules! try_opt {
+    ($e:expr) => {
+        match $e {
+            Some(v) => v,
+            None => return None,
+        }
+    };
+}
+
+/// A big-endian reader over TLS wire-format data.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Some(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|b| b[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Option<u16> {
+        self.bytes(2)
+            .map(|b| (u16::from(b[0]) << 8) | u16::from(b[1]))
+    }
+
+    pub(crate) fn u24(&mut self) -> Option<usize> {
+        self.bytes(3)
+            .map(|b| (usize::from(b[0]) << 16) | (usize::from(b[1]) << 8) | usize::from(b[2]))
+    }
+
+    /// Reads a vector with a one byte length prefix.
+    pub(crate) fn vec8(&mut self) -> Option<&'a [u8]> {
+        let len = try_opt!(self.u8());
+        self.bytes(usize::from(len))
+    }
+
+    /// Reads a vector with a two byte length prefix.
+    pub(crate) fn vec16(&mut self) -> Option<&'a [u8]> {
+        let len = try_opt!(self.u16());
+        self.bytes(usize::from(len))
+    }
+}
+
+/// Determines if a value is one of the GREASE values reserved by RFC 8701.
+pub(crate) fn is_grease(value: u16) -> bool {
+    value & 0x0f0f == 0x0a0a && value >> 8 == value & 0xff
+}
+
+/// Splits a buffer into big-endian 16 bit values.
+pub(crate) fn u16_list(data: &[u8]) -> Option<Vec<u16>> {
+    if data.len() % 2 != 0 {
+        return None;
+    }
+    Some(
+        data.chunks(2)
+            .map(|b| (u16::from(b[0]) << 8) | u16::from(b[1]))
+            .collect(),
+    )
+}
+
+/// Parses the body of a `supported_groups` extension.
+pub(crate) fn parse_supported_groups(data: &[u8]) -> Option<Vec<u16>> {
+    parse_vec16_u16_list(data)
+}
+
+/// Parses the body of an `ec_point_formats` extension.
+pub(crate) fn parse_ec_point_formats(data: &[u8]) -> Option<Vec<u8>> {
+    let mut reader = Reader::new(data);
+    let formats = try_opt!(reader.vec8());
+    if reader.is_empty() {
+        Some(formats.to_vec())
+    } else {
+        None
+    }
+}
+
+/// Parses the body of a `supported_versions` extension sent by a client.
+pub(crate) fn parse_supported_versions(data: &[u8]) -> Option<Vec<u16>> {
+    let mut reader = Reader::new(data);
+    let versions = try_opt!(reader.vec8());
+    if reader.is_empty() {
+        u16_list(versions)
+    } else {
+        None
+    }
+}
+
+/// Parses the body of a `signature_algorithms` extension.
+pub(crate) fn parse_signature_algorithms(data: &[u8]) -> Option<Vec<u16>> {
+    parse_vec16_u16_list(data)
+}
+
+fn parse_vec16_u16_list(data: &[u8]) -> Option<Vec<u16>> {
+    let mut reader = Reader::new(data);
+    let list = try_opt!(reader.vec16());
+    if reader.is_empty() {
+        u16_list(list)
+    } else {
+        None
+    }
+}
+
+/// Parses an extensions block, returning the type and body of each extension in order.
+pub(crate) fn parse_extensions(data: &[u8]) -> Option<Vec<(u16, &[u8])>> {
+    let mut reader = Reader::new(data);
+    let mut extensions = vec![];
+    while !reader.is_empty() {
+        let ext_type = try_opt!(reader.u16());
+        let body = try_opt!(reader.vec16());
+        extensions.push((ext_type, body));
+    }
+    Some(extensions)
+}
+
+/// Parses the body of a `server_name` extension, returning the first host name.
+fn parse_server_name(data: &[u8]) -> Option<&str> {
+    let mut reader = Reader::new(data);
+    let mut names = Reader::new(try_opt!(reader.vec16()));
+    if !reader.is_empty() {
+        return None;
+    }
+    while !names.is_empty() {
+        let name_type = try_opt!(names.u8());
+        let name = try_opt!(names.vec16());
+        if name_type == NAME_TYPE_HOST_NAME {
+            return str::from_utf8(name).ok();
+        }
+    }
+    None
+}
+
+/// Parses the body of an `application_layer_protocol_negotiation` extension.
+fn parse_alpn_protocols(data: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut reader = Reader::new(data);
+    let mut list = Reader::new(try_opt!(reader.vec16()));
+    if !reader.is_empty() {
+        return None;
+    }
+    let mut protocols = vec![];
+    while !list.is_empty() {
+        let protocol = try_opt!(list.vec8());
+        if protocol.is_empty() {
+            return None;
+        }
+        protocols.push(protocol);
+    }
+    Some(protocols)
+}
+
+/// An error returned by `ClientHello::parse`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientHelloError(&'static str);
+
+impl fmt::Display for ClientHelloError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "invalid ClientHello: {}", self.0)
+    }
+}
+
+impl Error for ClientHelloError {
+    fn description(&self) -> &str {
+        "an invalid ClientHello"
+    }
+}
+
+/// A `ClientHello` message parsed from the start of a raw TLS stream.
+///
+/// This allows the server name and ALPN protocols requested by a client to be inspected without
+/// terminating TLS, for example to route a connection in a TLS passthrough proxy. Its accessors
+/// return values in the same form as the `client_hello_*` methods of `SslRef`.
+#[derive(Debug, Clone)]
+pub struct ClientHello {
+    version: u16,
+    random: Vec<u8>,
+    session_id: Vec<u8>,
+    ciphers: Vec<u8>,
+    compression_methods: Vec<u8>,
+    extensions: Vec<(u16, Vec<u8>)>,
+}
+
+impl ClientHello {
+    /// Parses a `ClientHello` from the bytes read so far from the start of a TLS connection.
+    ///
+    /// `Ok(None)` is returned if more data is needed to parse the complete message, which may span
+    /// several records. The input is not modified, so once enough data has been read it can be
+    /// forwarded as-is. Callers should bound the amount of data they are willing to buffer.
+    ///
+    /// SSLv2-compatible hello messages are not supported.
+    pub fn parse(data: &[u8]) -> Result<Option<ClientHello>, ClientHelloError> {
+        if !data.is_empty() && data[0] != RECORD_HANDSHAKE {
+            return Err(ClientHelloError("not a handshake record"));
+        }
+
+        let mut reader = Reader::new(data);
+        let mut message = vec![];
+        loop {
+            let header = match reader.bytes(5) {
+                Some(header) => header,
+                None => return Ok(None),
+            };
+            if header[0] != RECORD_HANDSHAKE {
+                return Err(ClientHelloError("not a handshake record"));
+            }
+            if header[1] != 3 {
+                return Err(ClientHelloError("unsupported record version"));
+            }
+            let len = (usize::from(header[3]) << 8) | usize::from(header[4]);
+            if len == 0 || len > MAX_FRAGMENT_LEN {
+                return Err(ClientHelloError("invalid record length"));
+            }
+            match reader.bytes(len) {
+                Some(fragment) => message.extend_from_slice(fragment),
+                None => return Ok(None),
+            }
+
+            if message[0] != HANDSHAKE_CLIENT_HELLO {
+                return Err(ClientHelloError("not a ClientHello"));
+            }
+            if message.len() < 4 {
+                continue;
+            }
+            let len = (usize::from(message[1]) << 16)
+                | (usize::from(message[2]) << 8)
+                | usize::from(message[3]);
+            if message.len() - 4 > len {
+                return Err(ClientHelloError("trailing data after ClientHello"));
+            }
+            if message.len() - 4 == len {
+                return ClientHello::parse_body(&message[4..])
+                    .map(Some)
+                    .ok_or(ClientHelloError("malformed ClientHello"));
+            }
+        }
+    }
+
+    fn parse_body(data: &[u8]) -> Option<ClientHello> {
+        let mut reader = Reader::new(data);
+        let version = try_opt!(reader.u16());
+        let random = try_opt!(reader.bytes(32));
+        let session_id = try_opt!(reader.vec8());
+        let ciphers = try_opt!(reader.vec16());
+        if ciphers.len() % 2 != 0 {
+            return None;
+        }
+        let compression_methods = try_opt!(reader.vec8());
+        let extensions = if reader.is_empty() {
+            vec![]
+        } else {
+            let extensions = try_opt!(parse_extensions(try_opt!(reader.vec16())));
+            if !reader.is_empty() {
+                return None;
+            }
+            extensions
+                .into_iter()
+                .map(|(t, body)| (t, body.to_vec()))
+                .collect()
+        };
+
+        Some(ClientHello {
+            version,
+            random: random.to_vec(),
+            session_id: session_id.to_vec(),
+            ciphers: ciphers.to_vec(),
+            compression_methods: compression_methods.to_vec(),
+            extensions,
+        })
+    }
+
+    /// Returns the legacy version field of the message.
+    pub fn legacy_version(&self) -> SslVersion {
+        SslVersion(c_int::from(self.version))
+    }
+
+    /// Returns the random field of the message.
+    pub fn random(&self) -> &[u8] {
+        &self.random
+    }
+
+    /// Returns the session ID field of the message.
+    pub fn session_id(&self) -> &[u8] {
+        &self.session_id
+    }
+
+    /// Returns the ciphers field of the message, as a sequence of two byte cipher suite IDs.
+    pub fn ciphers(&self) -> &[u8] {
+        &self.ciphers
+    }
+
+    /// Returns the compression methods field of the message.
+    pub fn compression_methods(&self) -> &[u8] {
+        &self.compression_methods
+    }
+
+    /// Returns the types of the extensions in the message, in the order they were sent.
+    ///
+    /// Unlike `SslRef::client_hello_extensions_present`, this includes every extension sent.
+    pub fn extensions_present(&self) -> Vec<u16> {
+        self.extensions.iter().map(|&(t, _)| t).collect()
+    }
+
+    /// Returns the body of an extension, or `None` if it was not sent.
+    pub fn ext(&self, ext_type: u16) -> Option<&[u8]> {
+        self.extensions
+            .iter()
+            .find(|&&(t, _)| t == ext_type)
+            .map(|e| &*e.1)
+    }
+
+    /// Returns the host name sent in the `server_name` extension.
+    ///
+    /// `None` is returned if the extension is missing or malformed.
+    pub fn server_name(&self) -> Option<&str> {
+        self.ext(EXT_SERVER_NAME).and_then(parse_server_name)
+    }
+
+    /// Returns the protocols listed in the `application_layer_protocol_negotiation` extension.
+    ///
+    /// `None` is returned if the extension is missing or malformed.
+    pub fn alpn_protocols(&self) -> Option<Vec<&[u8]>> {
+        self.ext(EXT_ALPN).and_then(parse_alpn_protocols)
+    }
+
+    /// Returns the groups listed in the `supported_groups` extension, as their IANA code points.
+    ///
+    /// `None` is returned if the extension is missing or malformed.
+    pub fn supported_groups(&self) -> Option<Vec<u16>> {
+        self.ext(EXT_SUPPORTED_GROUPS)
+            .and_then(parse_supported_groups)
+    }
+
+    /// Returns the point formats listed in the `ec_point_formats` extension.
+    ///
+    /// `None` is returned if the extension is missing or malformed.
+    pub fn ec_point_formats(&self) -> Option<Vec<u8>> {
+        self.ext(EXT_EC_POINT_FORMATS)
+            .and_then(parse_ec_point_formats)
+    }
+
+    /// Returns the protocol versions listed in the `supported_versions` extension.
+    ///
+    /// `None` is returned if the extension is missing or malformed.
+    pub fn supported_versions(&self) -> Option<Vec<u16>> {
+        self.ext(EXT_SUPPORTED_VERSIONS)
+            .and_then(parse_supported_versions)
+    }
+
+    /// Returns the signature schemes listed in the `signature_algorithms` extension, as their
+    /// IANA code points.
+    ///
+    /// `None` is returned if the extension is missing or malformed.
+    pub fn signature_algorithms(&self) -> Option<Vec<u16>> {
+        self.ext(EXT_SIGNATURE_ALGORITHMS)
+            .and_then(parse_signature_algorithms)
+    }
+
+    /// Returns the JA3 fingerprint of the message.
+    pub fn ja3(&self) -> Ja3Fingerprint {
+        let ciphers = u16_list(&self.ciphers).unwrap_or_default();
+        Ja3Fingerprint::client(
+            self.version,
+            &ciphers,
+            &self.extensions_present(),
+            &self.supported_groups().unwrap_or_default(),
+            &self.ec_point_formats().unwrap_or_default(),
+        )
+    }
+}
+
+/// A [JA3] or JA3S fingerprint of a TLS handshake.
+///
+/// A JA3 fingerprint identifies a client by the version, cipher suites, extensions, supported
+/// groups, and point formats of its `ClientHello`. A JA3S fingerprint identifies a server by the
+/// version, cipher suite, and extensions of its `ServerHello`. GREASE values are ignored.
+///
+/// The `Display` implementation formats the fingerprint string, such as
+/// `771,4866-4867,0-10-11,29-23,0`.
+///
+/// [JA3]: https://github.com/salesforce/ja3
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ja3Fingerprint {
+    string: String,
+}
+
+impl Ja3Fingerprint {
+    /// Creates a JA3 fingerprint from the fields of a `ClientHello`.
+    ///
+    /// All values are given as their wire-format code points.
+    pub fn client(
+        version: u16,
+        ciphers: &[u16],
+        extensions: &[u16],
+        groups: &[u16],
+        point_formats: &[u8],
+    ) -> Ja3Fingerprint {
+        let point_formats = point_formats
+            .iter()
+            .map(|&f| u16::from(f))
+            .collect::<Vec<_>>();
+        let string = format!(
+            "{},{},{},{},{}",
+            version,
+            join(ciphers),
+            join(extensions),
+            join(groups),
+            join(&point_formats)
+        );
+        Ja3Fingerprint { string }
+    }
+
+    /// Creates a JA3S fingerprint from the fields of a `ServerHello`.
+    pub fn server(version: u16, cipher: u16, extensions: &[u16]) -> Ja3Fingerprint {
+        let string = format!("{},{},{}", version, cipher, join(extensions));
+        Ja3Fingerprint { string }
+    }
+
+    /// Creates a JA3S fingerprint from a raw `ServerHello` handshake message, including its four
+    /// byte handshake header.
+    ///
+    /// This is the form in which the message is passed to a callback registered with
+    /// `SslContextBuilder::set_msg_callback`. `None` is returned if the message is not a
+    /// well-formed `ServerHello`.
+    pub fn from_server_hello(message: &[u8]) -> Option<Ja3Fingerprint> {
+        let mut reader = Reader::new(message);
+        if try_opt!(reader.u8()) != HANDSHAKE_SERVER_HELLO {
+            return None;
+        }
+        let len = try_opt!(reader.u24());
+        let mut reader = Reader::new(try_opt!(reader.bytes(len)));
+
+        let version = try_opt!(reader.u16());
+        try_opt!(reader.bytes(32));
+        try_opt!(reader.vec8());
+        let cipher = try_opt!(reader.u16());
+        try_opt!(reader.u8());
+        let extensions = if reader.is_empty() {
+            vec![]
+        } else {
+            let extensions = try_opt!(parse_extensions(try_opt!(reader.vec16())));
+            if !reader.is_empty() {
+                return None;
+            }
+            extensions.iter().map(|&(t, _)| t).collect()
+        };
+
+        Some(Ja3Fingerprint::server(version, cipher, &extensions))
+    }
+
+    /// Returns the fingerprint string.
+    pub fn as_str(&self) -> &str {
+        &self.string
+    }
+
+    /// Returns the hex-encoded MD5 digest of the fingerprint string, as conventionally used to
+    /// compare fingerprints.
+    pub fn md5(&self) -> Result<String, ErrorStack> {
+        let digest = hash(MessageDigest::md5(), self.string.as_bytes())?;
+        Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+}
+
+impl fmt::Display for Ja3Fingerprint {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(&self.string)
+    }
+}
+
+fn join(values: &[u16]) -> String {
+    values
+        .iter()
+        .filter(|&&v| !is_grease(v))
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join("-")
+}
@@ -1,13 +1,21 @@
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
 use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
 
 use dh::Dh;
 use error::ErrorStack;
+#[cfg(ossl111)]
+use ssl::SslVersion;
 use ssl::{
-    HandshakeError, Ssl, SslContext, SslContextBuilder, SslMethod, SslMode, SslOptions, SslRef,
-    SslStream, SslVerifyMode,
+    HandshakeError, SpiffeVerifier, Ssl, SslContext, SslContextBuilder, SslMethod, SslMode,
+    SslOptions, SslRef, SslSession, SslSessionCacheMode, SslStream, SslVerifyMode, VirtualHosts,
 };
 use version;
+use x509::X509VerifyResult;
+
+// The number of TLS 1.3 tickets kept for each server.
+const MAX_TICKETS: usize = 4;
 
 fn ctx(method: SslMethod) -> Result<SslContextBuilder, ErrorStack> {
     let mut ctx = SslContextBuilder::new(method)?;
@@ -87,12 +95,156 @@ impl SslConnector {
 pub struct SslConnectorBuilder(SslContextBuilder);
 
 impl SslConnectorBuilder {
+    /// Authenticates servers by the SPIFFE ID in their certificate.
+    ///
+    /// SPIFFE certificates do not typically contain DNS names, so hostname verification should
+    /// usually be disabled with `ConnectConfiguration::verify_hostname`. This replaces any
+    /// previously configured verify callback.
+    pub fn set_spiffe_verifier(&mut self, verifier: SpiffeVerifier) {
+        self.0
+            .set_verify_callback(SslVerifyMode::PEER, move |ok, ctx| verifier.verify(ok, ctx));
+    }
+
+    /// Enables automatic session resumption, storing sessions for up to `max_servers` servers.
+    ///
+    /// Sessions are stored per host name when they are received from the server, and used to
+    /// resume later connections to the same host name. Only sessions of connections which used
+    /// SNI, hostname verification, and whose certificate chain verified successfully are stored,
+    /// and they are only used for connections with the same settings. Servers on different ports
+    /// of the same host share sessions, as they share a certificate. A TLS 1.2 session is reused
+    /// until the server replaces it, while TLS 1.3 tickets are only used once, so several are kept
+    /// for each server. Once sessions for `max_servers` servers are stored, those of the least
+    /// recently used server are discarded.
+    ///
+    /// `SslRef::session_reused` indicates whether a connection was resumed. This replaces any
+    /// previously configured new session callback.
+    pub fn set_session_store(&mut self, max_servers: usize) {
+        let store = Arc::new(SessionStore::new(max_servers));
+
+        self.0
+            .set_session_cache_mode(SslSessionCacheMode::CLIENT | SslSessionCacheMode::NO_INTERNAL);
+
+        let callback_store = store.clone();
+        self.0.set_new_session_callback(move |ssl, session| {
+            if let Some(key) = ssl.ex_data(Ssl::cached_ex_index::<SessionKey>()) {
+                if key.sni && key.verify_hostname && ssl.verify_result() == X509VerifyResult::OK {
+                    callback_store.insert(key, session, single_use(ssl));
+                }
+            }
+        });
+
+        self.0
+            .set_ex_data(SslContext::cached_ex_index::<Arc<SessionStore>>(), store);
+    }
+
     /// Consumes the builder, returning an `SslConnector`.
     pub fn build(self) -> SslConnector {
         SslConnector(self.0.build())
     }
 }
 
+#[cfg(ossl111)]
+fn single_use(ssl: &SslRef) -> bool {
+    ssl.version2() == Some(SslVersion::TLS1_3)
+}
+
+#[cfg(not(ossl111))]
+fn single_use(_: &SslRef) -> bool {
+    false
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct SessionKey {
+    host: String,
+    sni: bool,
+    verify_hostname: bool,
+}
+
+struct ServerSessions {
+    sessions: VecDeque<SslSession>,
+    single_use: bool,
+    last_used: u64,
+}
+
+struct SessionStoreState {
+    servers: HashMap<SessionKey, ServerSessions>,
+    tick: u64,
+}
+
+/// Client-side sessions, keyed by the server they were received from.
+struct SessionStore {
+    state: Mutex<SessionStoreState>,
+    max_servers: usize,
+}
+
+impl SessionStore {
+    fn new(max_servers: usize) -> SessionStore {
+        SessionStore {
+            state: Mutex::new(SessionStoreState {
+                servers: HashMap::new(),
+                tick: 0,
+            }),
+            max_servers,
+        }
+    }
+
+    fn insert(&self, key: &SessionKey, session: SslSession, single_use: bool) {
+        if self.max_servers == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let tick = state.tick;
+        state.tick += 1;
+
+        if !state.servers.contains_key(key) && state.servers.len() >= self.max_servers {
+            let oldest = state
+                .servers
+                .iter()
+                .min_by_key(|&(_, s)| s.last_used)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                state.servers.remove(&oldest);
+            }
+        }
+
+        let server = state
+            .servers
+            .entry(key.clone())
+            .or_insert_with(|| ServerSessions {
+                sessions: VecDeque::new(),
+                single_use,
+                last_used: tick,
+            });
+        server.last_used = tick;
+        if !single_use || !server.single_use {
+            server.sessions.clear();
+        }
+        server.single_use = single_use;
+        server.sessions.push_back(session);
+        while server.sessions.len() > MAX_TICKETS {
+            server.sessions.pop_front();
+        }
+    }
+
+    fn take(&self, key: &SessionKey) -> Option<SslSession> {
+        let mut state = self.state.lock().unwrap();
+        let tick = state.tick;
+        state.tick += 1;
+
+        let server = match state.servers.get_mut(key) {
+            Some(server) => server,
+            None => return None,
+        };
+        server.last_used = tick;
+        if server.single_use {
+            server.sessions.pop_back()
+        } else {
+            server.sessions.back().map(|s| s.to_owned())
+        }
+    }
+}
+
 impl Deref for SslConnectorBuilder {
     type Target = SslContextBuilder;
 
@@ -147,13 +299,11 @@ impl ConnectConfiguration {
         self.verify_hostname = verify_hostname;
     }
 
-    /// Initiates a client-side TLS session on a stream.
+    /// Returns an `Ssl` configured to connect to the provided domain.
     ///
-    /// The domain is used for SNI and hostname verification if enabled.
-    pub fn connect<S>(mut self, domain: &str, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
-    where
-        S: Read + Write,
-    {
+    /// The domain is used for SNI and hostname verification if enabled. If the connector has a
+    /// session store, a session for the domain is resumed.
+    pub fn into_ssl(mut self, domain: &str) -> Result<Ssl, ErrorStack> {
         if self.sni {
             self.ssl.set_hostname(domain)?;
         }
@@ -162,7 +312,37 @@ impl ConnectConfiguration {
             setup_verify_hostname(&mut self.ssl, domain)?;
         }
 
-        self.ssl.connect(stream)
+        let store = self
+            .ssl
+            .ssl_context()
+            .ex_data(SslContext::cached_ex_index::<Arc<SessionStore>>())
+            .cloned();
+        if let Some(store) = store {
+            let key = SessionKey {
+                host: domain.to_string(),
+                sni: self.sni,
+                verify_hostname: self.verify_hostname,
+            };
+            if let Some(session) = store.take(&key) {
+                // the session was received on a connection using the same context
+                unsafe { self.ssl.set_session(&session)? };
+            }
+            self.ssl
+                .set_ex_data(Ssl::cached_ex_index::<SessionKey>(), key);
+        }
+
+        Ok(self.ssl)
+    }
+
+    /// Initiates a client-side TLS session on a stream.
+    ///
+    /// The domain is used for SNI and hostname verification if enabled. If the connector has a
+    /// session store, a session for the domain is resumed.
+    pub fn connect<S>(self, domain: &str, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
+    where
+        S: Read + Write,
+    {
+        self.into_ssl(domain)?.connect(stream)
     }
 }
 
@@ -260,6 +440,26 @@ ssbzSibBsu/6iGtCOGEoXJf//////////wIBAg==
 pub struct SslAcceptorBuilder(SslContextBuilder);
 
 impl SslAcceptorBuilder {
+    /// Requires clients to present a certificate, and authenticates them by its SPIFFE ID.
+    ///
+    /// This replaces any previously configured verify callback.
+    pub fn set_spiffe_verifier(&mut self, verifier: SpiffeVerifier) {
+        self.0.set_verify_callback(
+            SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT,
+            move |ok, ctx| verifier.verify(ok, ctx),
+        );
+    }
+
+    /// Selects the context used for each connection from `hosts` by the server name sent by the
+    /// client.
+    ///
+    /// This replaces any previously configured servername callback. The matched host can be
+    /// retrieved with `SslRef::virtual_host` once the handshake completes.
+    pub fn set_virtual_hosts(&mut self, hosts: VirtualHosts) {
+        self.0
+            .set_servername_callback(move |ssl, alert| hosts.select(ssl, alert));
+    }
+
     /// Consumes the builder, returning a `SslAcceptor`.
     pub fn build(self) -> SslAcceptor {
         SslAcceptor(self.0.build())
@@ -0,0 +1,557 @@
+use ffi;
+use foreign_types::ForeignTypeRef;
+use std::cmp;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, UdpSocket};
+use std::ptr;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+#[cfg(ossl110)]
+use cvt_p;
+use ssl::handshake_info::timed_handshake;
+#[cfg(ossl110)]
+use ssl::SslContext;
+use ssl::{Error, ErrorCode, ShutdownResult, Ssl, SslRef, SslStream};
+
+// The largest possible UDP payload.
+const MAX_DATAGRAM_LEN: usize = 65507;
+
+// The number of datagrams buffered for each peer before new ones are dropped.
+const MAX_QUEUED_DATAGRAMS: usize = 256;
+
+struct DemuxState {
+    peers: HashMap<SocketAddr, VecDeque<Vec<u8>>>,
+    unknown: VecDeque<(SocketAddr, Vec<u8>)>,
+    reading: bool,
+}
+
+/// Routes datagrams received on a shared socket to the connection of the peer which sent them.
+///
+/// Whichever connection needs a datagram first reads from the socket on behalf of all of them.
+struct Demux {
+    socket: UdpSocket,
+    state: Mutex<DemuxState>,
+    cond: Condvar,
+}
+
+impl Demux {
+    fn new(socket: UdpSocket) -> Demux {
+        Demux {
+            socket,
+            state: Mutex::new(DemuxState {
+                peers: HashMap::new(),
+                unknown: VecDeque::new(),
+                reading: false,
+            }),
+            cond: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<DemuxState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn register(&self, peer: SocketAddr) {
+        let mut state = self.lock();
+        let mut queue = VecDeque::new();
+        let unknown = state
+            .unknown
+            .drain(..)
+            .filter_map(|(addr, buf)| {
+                if addr == peer {
+                    queue.push_back(buf);
+                    None
+                } else {
+                    Some((addr, buf))
+                }
+            })
+            .collect();
+        state.unknown = unknown;
+        state.peers.insert(peer, queue);
+    }
+
+    fn unregister(&self, peer: SocketAddr) {
+        self.lock().peers.remove(&peer);
+    }
+
+    /// Receives a datagram from the specified peer, or from any unregistered peer if `None`.
+    fn recv(
+        &self,
+        peer: Option<SocketAddr>,
+        timeout: Option<Duration>,
+    ) -> io::Result<(SocketAddr, Vec<u8>)> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let mut state = self.lock();
+
+        loop {
+            let next = match peer {
+                Some(peer) => state
+                    .peers
+                    .get_mut(&peer)
+                    .and_then(|q| q.pop_front())
+                    .map(|buf| (peer, buf)),
+                None => state.unknown.pop_front(),
+            };
+            if let Some(next) = next {
+                return Ok(next);
+            }
+
+            let remaining = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(io::Error::from(io::ErrorKind::WouldBlock));
+                    }
+                    Some(deadline - now)
+                }
+                None => None,
+            };
+
+            if state.reading {
+                state = match remaining {
+                    Some(remaining) => {
+                        self.cond
+                            .wait_timeout(state, remaining)
+                            .unwrap_or_else(|e| e.into_inner())
+                            .0
+                    }
+                    None => self.cond.wait(state).unwrap_or_else(|e| e.into_inner()),
+                };
+                continue;
+            }
+
+            state.reading = true;
+            drop(state);
+
+            let mut buf = vec![0; MAX_DATAGRAM_LEN];
+            let r = self
+                .socket
+                .set_read_timeout(remaining)
+                .and_then(|_| self.socket.recv_from(&mut buf));
+
+            state = self.lock();
+            state.reading = false;
+            self.cond.notify_all();
+
+            match r {
+                Ok((len, addr)) => {
+                    buf.truncate(len);
+                    match state.peers.get_mut(&addr) {
+                        Some(queue) => {
+                            if queue.len() < MAX_QUEUED_DATAGRAMS {
+                                queue.push_back(buf);
+                            }
+                        }
+                        None => {
+                            if state.unknown.len() < MAX_QUEUED_DATAGRAMS {
+                                state.unknown.push_back((addr, buf));
+                            }
+                        }
+                    }
+                }
+                Err(ref e)
+                    if e.kind() == io::ErrorKind::WouldBlock
+                        || e.kind() == io::ErrorKind::TimedOut => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// A datagram transport for a single peer over a shared socket.
+///
+/// Each read returns one datagram, and each write sends one datagram. Reads wait no longer than
+/// the read timeout or the DTLS retransmission timer of the connection, and timeouts are
+/// reported as `WouldBlock` errors so that OpenSSL treats them as retryable.
+struct DatagramChannel {
+    demux: Arc<Demux>,
+    peer: Option<SocketAddr>,
+    last_peer: Option<SocketAddr>,
+    ssl: *mut ffi::SSL,
+    read_timeout: Option<Duration>,
+}
+
+// the SSL pointer refers to the connection which owns the channel
+unsafe impl Send for DatagramChannel {}
+unsafe impl Sync for DatagramChannel {}
+
+impl DatagramChannel {
+    fn new(demux: Arc<Demux>) -> DatagramChannel {
+        DatagramChannel {
+            demux,
+            peer: None,
+            last_peer: None,
+            ssl: ptr::null_mut(),
+            read_timeout: None,
+        }
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        let timer = if self.ssl.is_null() {
+            None
+        } else {
+            unsafe { SslRef::from_ptr(self.ssl).dtls_timeout() }
+        };
+        match (timer, self.read_timeout) {
+            (Some(timer), Some(read_timeout)) => Some(cmp::min(timer, read_timeout)),
+            (timer, read_timeout) => timer.or(read_timeout),
+        }
+    }
+
+    fn connect(&mut self, peer: SocketAddr) {
+        self.demux.register(peer);
+        self.peer = Some(peer);
+    }
+}
+
+impl Drop for DatagramChannel {
+    fn drop(&mut self) {
+        if let Some(peer) = self.peer {
+            self.demux.unregister(peer);
+        }
+    }
+}
+
+impl Read for DatagramChannel {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (addr, datagram) = self.demux.recv(self.peer, self.timeout())?;
+        self.last_peer = Some(addr);
+        let len = cmp::min(buf.len(), datagram.len());
+        buf[..len].copy_from_slice(&datagram[..len]);
+        Ok(len)
+    }
+}
+
+impl Write for DatagramChannel {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.peer.or(self.last_peer) {
+            Some(peer) => self.demux.socket.send_to(buf, peer),
+            None => Err(io::Error::from(io::ErrorKind::NotConnected)),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs a DTLS operation, retransmitting handshake messages as the DTLS timer expires.
+fn drive<F, T>(
+    stream: &mut SslStream<DatagramChannel>,
+    read_timeout: Option<Duration>,
+    mut f: F,
+) -> Result<T, Error>
+where
+    F: FnMut(&mut SslStream<DatagramChannel>) -> Result<T, Error>,
+{
+    stream.get_mut().read_timeout = read_timeout;
+    loop {
+        match f(stream) {
+            Err(ref e)
+                if e.code() == ErrorCode::WANT_READ
+                    && e.io_error().map(|e| e.kind()) == Some(io::ErrorKind::WouldBlock)
+                    && stream.ssl().dtls_timeout() == Some(Duration::from_secs(0)) =>
+            {
+                stream.ssl.handle_dtls_timeout()?;
+            }
+            r => return r,
+        }
+    }
+}
+
+fn new_stream(ssl: Ssl, channel: DatagramChannel) -> SslStream<DatagramChannel> {
+    let mut stream = SslStream::new_base(ssl, channel);
+    stream.get_mut().ssl = stream.ssl().as_ptr();
+    stream
+}
+
+fn handshake(stream: &mut SslStream<DatagramChannel>) -> Result<(), Error> {
+    drive(stream, None, |s| {
+        let ret = unsafe { timed_handshake(s.ssl().as_ptr(), ffi::SSL_do_handshake) };
+        if ret > 0 {
+            Ok(())
+        } else {
+            Err(s.make_error(ret))
+        }
+    })
+}
+
+/// A DTLS server which accepts connections from many clients on a single `UdpSocket`.
+///
+/// Clients must complete a stateless cookie exchange before any per-connection state is
+/// allocated, so the `SslContext` must be configured with
+/// `SslContextBuilder::set_cookie_generate_cb` and `SslContextBuilder::set_cookie_verify_cb`.
+///
+/// Datagrams from established connections are routed to their `DtlsStream` by peer address.
+/// Reading from any connection or the listener receives datagrams on behalf of all of them, so
+/// the listener and its streams may be used from separate threads.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+#[cfg(ossl110)]
+pub struct DtlsListener {
+    demux: Arc<Demux>,
+    ctx: SslContext,
+    mtu: Option<u32>,
+}
+
+#[cfg(ossl110)]
+impl DtlsListener {
+    /// Creates a new listener which accepts connections on `socket`.
+    pub fn new(ctx: SslContext, socket: UdpSocket) -> DtlsListener {
+        DtlsListener {
+            demux: Arc::new(Demux::new(socket)),
+            ctx,
+            mtu: None,
+        }
+    }
+
+    /// Returns the local address of the listener's socket.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.demux.socket.local_addr()
+    }
+
+    /// Sets the MTU of accepted connections, excluding IP and UDP headers.
+    pub fn set_mtu(&mut self, mtu: u32) {
+        self.mtu = Some(mtu);
+    }
+
+    /// Waits for a client to complete a cookie exchange.
+    ///
+    /// Datagrams which do not contain a ClientHello with a valid cookie are answered with a
+    /// HelloVerifyRequest or discarded.
+    ///
+    /// The handshake is not performed, so that a slow or malicious client cannot stall the
+    /// listener. It should be completed with `MidHandshakeDtlsStream::handshake`, typically on
+    /// another thread.
+    ///
+    /// This corresponds to [`DTLSv1_listen`].
+    ///
+    /// [`DTLSv1_listen`]: https://www.openssl.org/docs/man1.1.0/ssl/DTLSv1_listen.html
+    pub fn accept(&self) -> Result<MidHandshakeDtlsStream, Error> {
+        let ssl = Ssl::new(&self.ctx)?;
+        unsafe {
+            ffi::SSL_set_accept_state(ssl.as_ptr());
+        }
+
+        let mut stream = new_stream(ssl, DatagramChannel::new(self.demux.clone()));
+
+        unsafe {
+            struct BioAddr(*mut ffi::BIO_ADDR);
+
+            impl Drop for BioAddr {
+                fn drop(&mut self) {
+                    unsafe { ffi::BIO_ADDR_free(self.0) }
+                }
+            }
+
+            let addr = BioAddr(cvt_p(ffi::BIO_ADDR_new())?);
+            loop {
+                match ffi::DTLSv1_listen(stream.ssl().as_ptr(), addr.0) {
+                    0 => {}
+                    n if n > 0 => break,
+                    n => return Err(stream.make_error(n)),
+                }
+            }
+        }
+
+        let peer = stream
+            .get_ref()
+            .last_peer
+            .expect("BUG: peer address missing");
+        stream.get_mut().connect(peer);
+
+        // DTLSv1_listen resets the MTU, so it has to be configured afterwards
+        if let Some(mtu) = self.mtu {
+            stream.ssl.set_mtu(mtu)?;
+        }
+
+        Ok(MidHandshakeDtlsStream { stream })
+    }
+}
+
+#[cfg(ossl110)]
+impl fmt::Debug for DtlsListener {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("DtlsListener")
+            .field("socket", &self.demux.socket)
+            .finish()
+    }
+}
+
+/// A DTLS connection accepted by a `DtlsListener` which has not yet completed its handshake.
+#[cfg(ossl110)]
+pub struct MidHandshakeDtlsStream {
+    stream: SslStream<DatagramChannel>,
+}
+
+#[cfg(ossl110)]
+impl MidHandshakeDtlsStream {
+    /// Returns the address of the peer.
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.stream
+            .get_ref()
+            .peer
+            .expect("BUG: peer address missing")
+    }
+
+    /// Returns a shared reference to the `Ssl` of the connection.
+    pub fn ssl(&self) -> &SslRef {
+        self.stream.ssl()
+    }
+
+    /// Returns a mutable reference to the `Ssl` of the connection.
+    pub fn ssl_mut(&mut self) -> &mut SslRef {
+        &mut self.stream.ssl
+    }
+
+    /// Performs the server-side handshake.
+    ///
+    /// The DTLS retransmission timer is driven automatically.
+    ///
+    /// This corresponds to [`SSL_accept`].
+    ///
+    /// [`SSL_accept`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_accept.html
+    pub fn handshake(mut self) -> Result<DtlsStream, Error> {
+        handshake(&mut self.stream)?;
+        Ok(DtlsStream {
+            stream: self.stream,
+            read_timeout: None,
+        })
+    }
+}
+
+#[cfg(ossl110)]
+impl fmt::Debug for MidHandshakeDtlsStream {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("MidHandshakeDtlsStream")
+            .field("peer", &self.stream.get_ref().peer)
+            .field("ssl", &self.ssl())
+            .finish()
+    }
+}
+
+/// A DTLS connection over a UDP socket.
+///
+/// Unlike `SslStream`, message boundaries are preserved: each call to `send` is delivered to the
+/// peer as a single record, and each call to `recv` returns the contents of a single record.
+pub struct DtlsStream {
+    stream: SslStream<DatagramChannel>,
+    read_timeout: Option<Duration>,
+}
+
+impl DtlsStream {
+    /// Performs a client-side handshake with `peer` over `socket`.
+    ///
+    /// The DTLS retransmission timer is driven automatically.
+    pub fn connect(ssl: Ssl, socket: UdpSocket, peer: SocketAddr) -> Result<DtlsStream, Error> {
+        unsafe {
+            ffi::SSL_set_connect_state(ssl.as_ptr());
+        }
+
+        let mut channel = DatagramChannel::new(Arc::new(Demux::new(socket)));
+        channel.connect(peer);
+        let mut stream = new_stream(ssl, channel);
+
+        handshake(&mut stream)?;
+        Ok(DtlsStream {
+            stream,
+            read_timeout: None,
+        })
+    }
+
+    /// Returns the address of the peer.
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.stream
+            .get_ref()
+            .peer
+            .expect("BUG: peer address missing")
+    }
+
+    /// Returns the local address of the underlying socket.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.get_ref().demux.socket.local_addr()
+    }
+
+    /// Sets the timeout for `recv`.
+    ///
+    /// If the timeout expires, an error with `ErrorCode::WANT_READ` is returned. Defaults to
+    /// `None`, which blocks indefinitely.
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) {
+        self.read_timeout = timeout;
+    }
+
+    /// Receives a single message from the peer.
+    ///
+    /// If `buf` is too small to hold the message, the remainder is returned by subsequent calls.
+    /// Once the peer has shut down the connection, an error with `ErrorCode::ZERO_RETURN` is
+    /// returned.
+    ///
+    /// This corresponds to [`SSL_read`].
+    ///
+    /// [`SSL_read`]: https://www.openssl.org/docs/manmaster/man3/SSL_read.html
+    pub fn recv(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        drive(&mut self.stream, self.read_timeout, |s| s.ssl_read(buf))
+    }
+
+    /// Sends a single message to the peer.
+    ///
+    /// The message must fit into a single datagram.
+    ///
+    /// This corresponds to [`SSL_write`].
+    ///
+    /// [`SSL_write`]: https://www.openssl.org/docs/manmaster/man3/SSL_write.html
+    pub fn send(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        drive(&mut self.stream, None, |s| s.ssl_write(buf))
+    }
+
+    /// Sends a close notify message to the peer.
+    ///
+    /// This corresponds to [`SSL_shutdown`].
+    ///
+    /// [`SSL_shutdown`]: https://www.openssl.org/docs/manmaster/man3/SSL_shutdown.html
+    pub fn shutdown(&mut self) -> Result<ShutdownResult, Error> {
+        self.stream.shutdown()
+    }
+
+    /// Returns a shared reference to the `Ssl` object associated with this stream.
+    pub fn ssl(&self) -> &SslRef {
+        self.stream.ssl()
+    }
+}
+
+impl fmt::Debug for DtlsStream {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("DtlsStream")
+            .field("peer", &self.stream.get_ref().peer)
+            .field("ssl", &self.ssl())
+            .finish()
+    }
+}
+
+impl Read for DtlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.recv(buf) {
+            Ok(n) => Ok(n),
+            Err(ref e) if e.code() == ErrorCode::ZERO_RETURN => Ok(0),
+            Err(e) => Err(e
+                .into_io_error()
+                .unwrap_or_else(|e| io::Error::new(io::ErrorKind::Other, e))),
+        }
+    }
+}
+
+impl Write for DtlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.send(buf).map_err(|e| {
+            e.into_io_error()
+                .unwrap_or_else(|e| io::Error::new(io::ErrorKind::Other, e))
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
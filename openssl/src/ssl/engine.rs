@@ -0,0 +1,217 @@
+use ffi;
+use foreign_types::ForeignTypeRef;
+use libc::c_int;
+use std::cmp;
+
+use error::ErrorStack;
+use ssl::error::InnerError;
+use ssl::handshake_info::timed_handshake;
+use ssl::{Error, ErrorCode, ShutdownResult, Ssl, SslRef};
+use cvt_p;
+
+/// A TLS connection which is driven by byte buffers rather than a `Read + Write` stream.
+///
+/// Ciphertext received from the peer is passed in with `read_tls`, and ciphertext which should be
+/// sent to the peer is retrieved with `write_tls`. The handshake, plaintext reads and writes, and
+/// shutdown work as they do for `SslStream`, except that instead of blocking they fail with
+/// `ErrorCode::WANT_READ` when more input is required. After every operation, any pending output
+/// should be drained and sent to the peer.
+///
+/// Internally, the connection reads from and writes to a pair of memory BIOs.
+pub struct SslEngine {
+    ssl: Ssl,
+    wants_read: bool,
+}
+
+impl SslEngine {
+    /// Creates a new engine for the connection.
+    ///
+    /// `set_connect_state` or `set_accept_state` must be called before the handshake begins.
+    pub fn new(ssl: Ssl) -> Result<SslEngine, ErrorStack> {
+        unsafe {
+            let rbio = cvt_p(ffi::BIO_new(ffi::BIO_s_mem()))?;
+            // an empty input buffer indicates that more data is needed rather than EOF
+            ffi::BIO_set_mem_eof_return(rbio, -1);
+            let wbio = match cvt_p(ffi::BIO_new(ffi::BIO_s_mem())) {
+                Ok(wbio) => wbio,
+                Err(e) => {
+                    ffi::BIO_free_all(rbio);
+                    return Err(e);
+                }
+            };
+            ffi::SSL_set_bio(ssl.as_ptr(), rbio, wbio);
+        }
+
+        Ok(SslEngine {
+            ssl,
+            wants_read: false,
+        })
+    }
+
+    /// Configures the engine as the client side of the connection.
+    ///
+    /// This corresponds to [`SSL_set_connect_state`].
+    ///
+    /// [`SSL_set_connect_state`]: https://www.openssl.org/docs/manmaster/man3/SSL_set_connect_state.html
+    pub fn set_connect_state(&mut self) {
+        unsafe { ffi::SSL_set_connect_state(self.ssl.as_ptr()) }
+    }
+
+    /// Configures the engine as the server side of the connection.
+    ///
+    /// This corresponds to [`SSL_set_accept_state`].
+    ///
+    /// [`SSL_set_accept_state`]: https://www.openssl.org/docs/manmaster/man3/SSL_set_accept_state.html
+    pub fn set_accept_state(&mut self) {
+        unsafe { ffi::SSL_set_accept_state(self.ssl.as_ptr()) }
+    }
+
+    /// Passes ciphertext received from the peer to the engine.
+    ///
+    /// All of the data is buffered until it is consumed by a subsequent operation.
+    pub fn read_tls(&mut self, buf: &[u8]) -> Result<(), ErrorStack> {
+        let mut buf = buf;
+        while !buf.is_empty() {
+            let len = cmp::min(c_int::max_value() as usize, buf.len()) as c_int;
+            unsafe {
+                let rbio = ffi::SSL_get_rbio(self.ssl.as_ptr());
+                let n = ffi::BIO_write(rbio, buf.as_ptr() as *const _, len);
+                if n <= 0 {
+                    return Err(ErrorStack::get());
+                }
+                buf = &buf[n as usize..];
+            }
+            self.wants_read = false;
+        }
+        Ok(())
+    }
+
+    /// Retrieves ciphertext which should be sent to the peer, returning the number of bytes
+    /// written into `buf`.
+    pub fn write_tls(&mut self, buf: &mut [u8]) -> usize {
+        if buf.is_empty() {
+            return 0;
+        }
+
+        let len = cmp::min(c_int::max_value() as usize, buf.len()) as c_int;
+        unsafe {
+            let wbio = ffi::SSL_get_wbio(self.ssl.as_ptr());
+            let n = ffi::BIO_read(wbio, buf.as_mut_ptr() as *mut _, len);
+            cmp::max(n, 0) as usize
+        }
+    }
+
+    /// Returns the number of bytes of ciphertext waiting to be retrieved with `write_tls`.
+    pub fn pending_tls(&self) -> usize {
+        unsafe { ffi::BIO_ctrl_pending(ffi::SSL_get_wbio(self.ssl.as_ptr())) as usize }
+    }
+
+    /// Returns `true` if there is ciphertext waiting to be sent to the peer.
+    pub fn wants_write(&self) -> bool {
+        self.pending_tls() > 0
+    }
+
+    /// Returns `true` if the last operation could not complete until more ciphertext is received
+    /// from the peer.
+    pub fn wants_read(&self) -> bool {
+        self.wants_read
+    }
+
+    /// Performs the TLS handshake, or continues a previously started one.
+    ///
+    /// This corresponds to [`SSL_do_handshake`].
+    ///
+    /// [`SSL_do_handshake`]: https://www.openssl.org/docs/manmaster/man3/SSL_do_handshake.html
+    pub fn handshake(&mut self) -> Result<(), Error> {
+        let ret = unsafe { timed_handshake(self.ssl.as_ptr(), ffi::SSL_do_handshake) };
+        if ret > 0 {
+            Ok(())
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Reads decrypted data received from the peer.
+    ///
+    /// A return value of `Ok(0)` is only possible for an empty `buf`. Once the peer has shut down
+    /// the connection, an error with `ErrorCode::ZERO_RETURN` is returned.
+    ///
+    /// This corresponds to [`SSL_read`].
+    ///
+    /// [`SSL_read`]: https://www.openssl.org/docs/manmaster/man3/SSL_read.html
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let ret = self.ssl.read(buf);
+        if ret > 0 {
+            Ok(ret as usize)
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Encrypts data to be sent to the peer.
+    ///
+    /// This corresponds to [`SSL_write`].
+    ///
+    /// [`SSL_write`]: https://www.openssl.org/docs/manmaster/man3/SSL_write.html
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let ret = self.ssl.write(buf);
+        if ret > 0 {
+            Ok(ret as usize)
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Shuts down the session.
+    ///
+    /// See `SslStream::shutdown` for details.
+    ///
+    /// This corresponds to [`SSL_shutdown`].
+    ///
+    /// [`SSL_shutdown`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_shutdown.html
+    pub fn shutdown(&mut self) -> Result<ShutdownResult, Error> {
+        match unsafe { ffi::SSL_shutdown(self.ssl.as_ptr()) } {
+            0 => Ok(ShutdownResult::Sent),
+            1 => Ok(ShutdownResult::Received),
+            n => Err(self.make_error(n)),
+        }
+    }
+
+    /// Returns a shared reference to the `Ssl` object associated with this engine.
+    pub fn ssl(&self) -> &SslRef {
+        &self.ssl
+    }
+
+    /// Returns a mutable reference to the `Ssl` object associated with this engine.
+    pub fn ssl_mut(&mut self) -> &mut SslRef {
+        &mut self.ssl
+    }
+
+    fn make_error(&mut self, ret: c_int) -> Error {
+        let code = self.ssl.get_error(ret);
+        self.wants_read = code == ErrorCode::WANT_READ;
+
+        let cause = match code {
+            ErrorCode::SSL => Some(InnerError::Ssl(ErrorStack::get())),
+            ErrorCode::SYSCALL => {
+                let errs = ErrorStack::get();
+                if errs.errors().is_empty() {
+                    None
+                } else {
+                    Some(InnerError::Ssl(errs))
+                }
+            }
+            _ => None,
+        };
+
+        Error { code, cause }
+    }
+}
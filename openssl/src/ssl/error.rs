@@ -41,6 +41,12 @@ impl ErrorCode {
     /// An error occurred in the SSL library.
     pub const SSL: ErrorCode = ErrorCode(ffi::SSL_ERROR_SSL);
 
+    /// The certificate callback indicated that it needed to be retried.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[cfg(ossl102)]
+    pub const WANT_X509_LOOKUP: ErrorCode = ErrorCode(ffi::SSL_ERROR_WANT_X509_LOOKUP);
+
     /// The client hello callback indicated that it needed to be retried.
     /// 
     /// Requires OpenSSL 1.1.1 or newer.
@@ -0,0 +1,337 @@
+use ffi;
+use foreign_types::ForeignTypeRef;
+use libc::c_int;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use ex_data::Index;
+use hash::MessageDigest;
+use nid::Nid;
+#[cfg(ossl111)]
+use ssl::SslEarlyDataStatus;
+use ssl::{NameType, Ssl, SslContext, SslContextBuilder, SslRef, SslVersion};
+use x509::{X509NameRef, X509VerifyResult};
+
+lazy_static! {
+    static ref TIMING_INDEX: Index<SslContext, HandshakeTiming> =
+        SslContext::new_ex_index().unwrap();
+    static ref TIMER_INDEX: Index<Ssl, HandshakeTimer> = Ssl::new_ex_index().unwrap();
+}
+
+struct HandshakeTiming(bool);
+
+struct HandshakeTimer {
+    start: Instant,
+    duration: Option<Duration>,
+}
+
+/// Drives the handshake with `f`, recording when it starts and how long it takes to complete if
+/// timing is enabled on the connection's context.
+pub(crate) unsafe fn timed_handshake(
+    ssl: *mut ffi::SSL,
+    f: unsafe extern "C" fn(*mut ffi::SSL) -> c_int,
+) -> c_int {
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let enabled = ssl
+        .ssl_context()
+        .ex_data(*TIMING_INDEX)
+        .map_or(false, |t| t.0);
+    if !enabled {
+        return f(ssl.as_ptr());
+    }
+
+    let index = *TIMER_INDEX;
+    if ssl.ex_data(index).is_none() {
+        ssl.set_ex_data(
+            index,
+            HandshakeTimer {
+                start: Instant::now(),
+                duration: None,
+            },
+        );
+    }
+
+    let ret = f(ssl.as_ptr());
+    if ret > 0 {
+        if let Some(timer) = ssl.ex_data_mut(index) {
+            if timer.duration.is_none() {
+                timer.duration = Some(timer.start.elapsed());
+            }
+        }
+    }
+    ret
+}
+
+/// A summary of the parameters negotiated by a handshake.
+///
+/// This is a snapshot taken by `SslRef::handshake_info`, intended for access logs and metrics.
+/// Values which are unknown, or not supported by the linked version of OpenSSL, are `None`.
+///
+/// The `Display` implementation formats the summary as a single line of space-separated
+/// `key=value` pairs, omitting unknown values.
+#[derive(Debug, Clone)]
+pub struct HandshakeInfo {
+    version: Option<SslVersion>,
+    version_str: &'static str,
+    cipher: Option<&'static str>,
+    cipher_standard_name: Option<&'static str>,
+    group: Option<Nid>,
+    peer_signature_type: Option<Nid>,
+    peer_signature_digest: Option<Nid>,
+    alpn_protocol: Option<Vec<u8>>,
+    server_name: Option<String>,
+    session_reused: bool,
+    #[cfg(ossl111)]
+    early_data_status: SslEarlyDataStatus,
+    peer_subject: Option<String>,
+    peer_fingerprint: Option<Vec<u8>>,
+    verify_result: X509VerifyResult,
+    duration: Option<Duration>,
+}
+
+impl HandshakeInfo {
+    /// Returns the protocol version.
+    pub fn version(&self) -> Option<SslVersion> {
+        self.version
+    }
+
+    /// Returns the protocol version as a string, such as `TLSv1.3`.
+    pub fn version_str(&self) -> &'static str {
+        self.version_str
+    }
+
+    /// Returns OpenSSL's name for the cipher, such as `ECDHE-RSA-AES128-GCM-SHA256`.
+    pub fn cipher(&self) -> Option<&'static str> {
+        self.cipher
+    }
+
+    /// Returns the IANA standard name for the cipher, such as
+    /// `TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256`.
+    ///
+    /// This is always `None` before OpenSSL 1.1.1.
+    pub fn cipher_standard_name(&self) -> Option<&'static str> {
+        self.cipher_standard_name
+    }
+
+    /// Returns the group used for key exchange, as reported by `SslRef::negotiated_group`.
+    ///
+    /// This is always `None` before OpenSSL 1.1.1.
+    pub fn group(&self) -> Option<Nid> {
+        self.group
+    }
+
+    /// Returns the public key algorithm used by the peer to sign the handshake.
+    ///
+    /// This is always `None` before OpenSSL 1.1.1.
+    pub fn peer_signature_type(&self) -> Option<Nid> {
+        self.peer_signature_type
+    }
+
+    /// Returns the digest used by the peer to sign the handshake.
+    ///
+    /// This is always `None` before OpenSSL 1.0.2.
+    pub fn peer_signature_digest(&self) -> Option<Nid> {
+        self.peer_signature_digest
+    }
+
+    /// Returns the protocol selected via ALPN.
+    ///
+    /// This is always `None` before OpenSSL 1.0.2 or LibreSSL 2.6.1.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_ref().map(|p| &**p)
+    }
+
+    /// Returns the server name sent by the client via SNI.
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_ref().map(|s| &**s)
+    }
+
+    /// Determines if the session was resumed.
+    pub fn session_reused(&self) -> bool {
+        self.session_reused
+    }
+
+    /// Returns whether early data was sent and accepted.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn early_data_status(&self) -> SslEarlyDataStatus {
+        self.early_data_status
+    }
+
+    /// Returns the subject name of the peer's leaf certificate, formatted as comma-separated
+    /// `name=value` pairs such as `CN=foobar.com, O=Example`.
+    pub fn peer_subject(&self) -> Option<&str> {
+        self.peer_subject.as_ref().map(|s| &**s)
+    }
+
+    /// Returns the SHA-256 digest of the DER encoding of the peer's leaf certificate.
+    pub fn peer_fingerprint(&self) -> Option<&[u8]> {
+        self.peer_fingerprint.as_ref().map(|f| &**f)
+    }
+
+    /// Returns the result of verifying the peer's certificate.
+    pub fn verify_result(&self) -> X509VerifyResult {
+        self.verify_result
+    }
+
+    /// Returns the time taken by the handshake.
+    ///
+    /// This is measured from the first call to a handshake method, such as `Ssl::connect` or
+    /// `MidHandshakeSslStream::handshake`, to the completion of the handshake, so it includes
+    /// time spent waiting for the peer. It is `None` unless timing was enabled with
+    /// `SslContextBuilder::set_handshake_timing`, or if the handshake was performed implicitly,
+    /// such as by `SslStream::read`.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+}
+
+impl fmt::Display for HandshakeInfo {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "protocol={}", self.version_str)?;
+        if let Some(cipher) = self.cipher {
+            write!(fmt, " cipher={}", cipher)?;
+        }
+        if let Some(name) = self.cipher_standard_name {
+            write!(fmt, " cipher_std={}", name)?;
+        }
+        if let Some(group) = self.group.and_then(|n| n.short_name().ok()) {
+            write!(fmt, " group={}", group)?;
+        }
+        if let Some(signature) = self.peer_signature_type.and_then(|n| n.short_name().ok()) {
+            write!(fmt, " peer_sig={}", signature)?;
+            if let Some(digest) = self.peer_signature_digest.and_then(|n| n.short_name().ok()) {
+                write!(fmt, "+{}", digest)?;
+            }
+        } else if let Some(digest) = self.peer_signature_digest.and_then(|n| n.short_name().ok()) {
+            write!(fmt, " peer_sig_digest={}", digest)?;
+        }
+        if let Some(ref protocol) = self.alpn_protocol {
+            write!(fmt, " alpn={}", String::from_utf8_lossy(protocol))?;
+        }
+        if let Some(ref name) = self.server_name {
+            write!(fmt, " sni={}", name)?;
+        }
+        write!(fmt, " resumed={}", self.session_reused)?;
+        if let Some(status) = self.early_data_str() {
+            write!(fmt, " early_data={}", status)?;
+        }
+        if let Some(ref subject) = self.peer_subject {
+            write!(fmt, " peer={:?}", subject)?;
+        }
+        if let Some(ref fingerprint) = self.peer_fingerprint {
+            fmt.write_str(" peer_sha256=")?;
+            for byte in fingerprint {
+                write!(fmt, "{:02x}", byte)?;
+            }
+        }
+        write!(fmt, " verify={:?}", self.verify_result.error_string())?;
+        if let Some(duration) = self.duration {
+            let millis =
+                duration.as_secs() as f64 * 1000. + f64::from(duration.subsec_nanos()) / 1e6;
+            write!(fmt, " duration_ms={:.3}", millis)?;
+        }
+        Ok(())
+    }
+}
+
+impl HandshakeInfo {
+    #[cfg(ossl111)]
+    fn early_data_str(&self) -> Option<&'static str> {
+        let status = match self.early_data_status {
+            SslEarlyDataStatus::ACCEPTED => "accepted",
+            SslEarlyDataStatus::REJECTED => "rejected",
+            _ => "not_sent",
+        };
+        Some(status)
+    }
+
+    #[cfg(not(ossl111))]
+    fn early_data_str(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+fn format_name(name: &X509NameRef) -> String {
+    let mut out = String::new();
+    for entry in name.entries() {
+        if !out.is_empty() {
+            out.push_str(", ");
+        }
+        let object = entry.object();
+        match object.nid().short_name() {
+            Ok(name) => out.push_str(name),
+            Err(_) => out.push_str(&object.to_string()),
+        }
+        out.push('=');
+        match entry.data().as_utf8() {
+            Ok(value) => out.push_str(&value),
+            Err(_) => out.push('?'),
+        }
+    }
+    out
+}
+
+impl SslRef {
+    /// Returns a summary of the parameters negotiated by the handshake.
+    ///
+    /// This should be called once the handshake has completed.
+    pub fn handshake_info(&self) -> HandshakeInfo {
+        let cipher = self.current_cipher();
+        let peer = self.peer_certificate();
+
+        HandshakeInfo {
+            version: self.version2(),
+            version_str: self.version_str(),
+            cipher: cipher.map(|c| c.name()),
+            #[cfg(ossl111)]
+            cipher_standard_name: cipher.and_then(|c| c.standard_name()),
+            #[cfg(not(ossl111))]
+            cipher_standard_name: None,
+            #[cfg(ossl111)]
+            group: self.negotiated_group(),
+            #[cfg(not(ossl111))]
+            group: None,
+            #[cfg(ossl111)]
+            peer_signature_type: self.peer_signature_type(),
+            #[cfg(not(ossl111))]
+            peer_signature_type: None,
+            #[cfg(ossl102)]
+            peer_signature_digest: self.peer_signature_digest(),
+            #[cfg(not(ossl102))]
+            peer_signature_digest: None,
+            #[cfg(any(ossl102, libressl261))]
+            alpn_protocol: self.selected_alpn_protocol().map(|p| p.to_vec()),
+            #[cfg(not(any(ossl102, libressl261)))]
+            alpn_protocol: None,
+            server_name: self.servername(NameType::HOST_NAME).map(str::to_string),
+            session_reused: self.session_reused(),
+            #[cfg(ossl111)]
+            early_data_status: self.early_data_status(),
+            peer_subject: peer.as_ref().map(|c| format_name(c.subject_name())),
+            peer_fingerprint: peer
+                .as_ref()
+                .and_then(|c| c.digest(MessageDigest::sha256()).ok())
+                .map(|d| d.to_vec()),
+            verify_result: self.verify_result(),
+            duration: self.ex_data(*TIMER_INDEX).and_then(|t| t.duration),
+        }
+    }
+}
+
+impl SslContextBuilder {
+    /// Enables measuring the duration of handshakes, as reported by `HandshakeInfo::duration`.
+    ///
+    /// Timing stores extra data on each connection, so it is disabled by default.
+    pub fn set_handshake_timing(&mut self, enabled: bool) {
+        unsafe {
+            let data = ffi::SSL_CTX_get_ex_data(self.as_ptr(), TIMING_INDEX.as_raw());
+            if data.is_null() {
+                self.set_ex_data(*TIMING_INDEX, HandshakeTiming(enabled));
+            } else {
+                (*(data as *mut HandshakeTiming)).0 = enabled;
+            }
+        }
+    }
+}
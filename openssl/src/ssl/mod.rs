@@ -59,7 +59,7 @@
 //! ```
 use ffi;
 use foreign_types::{ForeignType, ForeignTypeRef, Opaque};
-use libc::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong, c_void};
+use libc::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong, c_void, timeval};
 use std::any::TypeId;
 use std::cmp;
 use std::collections::HashMap;
@@ -76,7 +76,9 @@ use std::ptr;
 use std::slice;
 use std::str;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use bio::MemBio;
 use dh::{Dh, DhRef};
 #[cfg(all(ossl101, not(ossl110)))]
 use ec::EcKey;
@@ -85,14 +87,20 @@ use error::ErrorStack;
 use ex_data::Index;
 #[cfg(ossl111)]
 use hash::MessageDigest;
-#[cfg(ossl110)]
+#[cfg(ossl102)]
 use nid::Nid;
-use pkey::{HasPrivate, PKeyRef, Params, Private};
-use srtp::{SrtpProtectionProfile, SrtpProtectionProfileRef};
+#[cfg(ossl111)]
+use pkey::Id;
+#[cfg(ossl102)]
+use pkey::Public;
+use pkey::{HasPrivate, PKey, PKeyRef, Params, Private};
+use srtp::{SrtpKeyingMaterial, SrtpProtectionProfile, SrtpProtectionProfileRef};
 use ssl::bio::BioMethod;
 use ssl::callbacks::*;
 use ssl::error::InnerError;
-use stack::{Stack, StackRef};
+use ssl::handshake_info::timed_handshake;
+use ssl::session_cache::session_timeout;
+use stack::{Stack, StackRef, Stackable};
 #[cfg(ossl102)]
 use x509::store::X509Store;
 use x509::store::{X509StoreBuilderRef, X509StoreRef};
@@ -101,15 +109,43 @@ use x509::verify::X509VerifyParamRef;
 use x509::{X509Name, X509Ref, X509StoreContextRef, X509VerifyResult, X509};
 use {cvt, cvt_n, cvt_p, init};
 
+pub use ssl::client_hello::{ClientHello, ClientHelloError, Ja3Fingerprint};
 pub use ssl::connector::{
     ConnectConfiguration, SslAcceptor, SslAcceptorBuilder, SslConnector, SslConnectorBuilder,
 };
+#[cfg(feature = "futures-io")]
+pub use ssl::async_stream::{AsyncSslStream, HandshakeFuture, ShutdownFuture};
+#[cfg(ossl110)]
+pub use ssl::dtls::{DtlsListener, MidHandshakeDtlsStream};
+pub use ssl::dtls::DtlsStream;
+pub use ssl::engine::SslEngine;
 pub use ssl::error::{Error, ErrorCode, HandshakeError};
+pub use ssl::handshake_info::HandshakeInfo;
+pub use ssl::session_cache::{MemorySessionCache, SessionCache};
+pub use ssl::spiffe::{SpiffeId, SpiffeVerifier};
+#[cfg(feature = "log")]
+pub use ssl::trace::{log_info, log_message};
+pub use ssl::trace::{MessageDirection, SslContentType, SslInfoEvent, SslMessage};
+pub use ssl::ticket::{
+    SessionTicketKey, SessionTicketKeys, TicketKeyRequest, TicketKeyResponse,
+};
+pub use ssl::vhost::{InvalidHostName, VirtualHosts};
 
+#[cfg(feature = "futures-io")]
+mod async_stream;
 mod bio;
 mod callbacks;
+mod client_hello;
 mod connector;
+mod dtls;
+mod engine;
 mod error;
+mod handshake_info;
+mod session_cache;
+mod spiffe;
+mod ticket;
+mod trace;
+mod vhost;
 #[cfg(test)]
 mod test;
 
@@ -330,6 +366,38 @@ bitflags! {
         ///
         /// This should be paired with `SSL_VERIFY_PEER`. It has no effect on the client side.
         const FAIL_IF_NO_PEER_CERT = ffi::SSL_VERIFY_FAIL_IF_NO_PEER_CERT;
+
+        /// On the server side, do not request a certificate from the client during the initial
+        /// handshake, but only when `SslRef::verify_client_post_handshake` is called.
+        ///
+        /// This should be paired with `SSL_VERIFY_PEER`. It has no effect on the client side.
+        ///
+        /// Requires OpenSSL 1.1.1 or newer.
+        #[cfg(ossl111)]
+        const POST_HANDSHAKE = ffi::SSL_VERIFY_POST_HANDSHAKE;
+    }
+}
+
+#[cfg(ossl102)]
+bitflags! {
+    /// Options controlling how `SslContextBuilder::build_cert_chain` builds a certificate chain.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    pub struct SslBuildChainFlags: c_long {
+        /// Use the existing chain as untrusted certificates rather than the certificate store.
+        const UNTRUSTED = ffi::SSL_BUILD_CHAIN_FLAG_UNTRUSTED;
+
+        /// Omit the root certificate from the chain.
+        const NO_ROOT = ffi::SSL_BUILD_CHAIN_FLAG_NO_ROOT;
+
+        /// Only check the existing chain rather than replacing it.
+        const CHECK = ffi::SSL_BUILD_CHAIN_FLAG_CHECK;
+
+        /// Ignore verification errors when building the chain.
+        const IGNORE_ERROR = ffi::SSL_BUILD_CHAIN_FLAG_IGNORE_ERROR;
+
+        /// Clear the verification error state if `IGNORE_ERROR` is set.
+        const CLEAR_ERROR = ffi::SSL_BUILD_CHAIN_FLAG_CLEAR_ERROR;
     }
 }
 
@@ -500,10 +568,26 @@ impl SniError {
 pub struct SslAlert(c_int);
 
 impl SslAlert {
+    /// Alert 0 - `close_notify`.
+    pub const CLOSE_NOTIFY: SslAlert = SslAlert(ffi::SSL_AD_CLOSE_NOTIFY);
+    /// Alert 40 - `handshake_failure`.
+    pub const HANDSHAKE_FAILURE: SslAlert = SslAlert(ffi::SSL_AD_HANDSHAKE_FAILURE);
     /// Alert 112 - `unrecognized_name`.
     pub const UNRECOGNIZED_NAME: SslAlert = SslAlert(ffi::SSL_AD_UNRECOGNIZED_NAME);
     pub const ILLEGAL_PARAMETER: SslAlert = SslAlert(ffi::SSL_AD_ILLEGAL_PARAMETER);
     pub const DECODE_ERROR: SslAlert = SslAlert(ffi::SSL_AD_DECODE_ERROR);
+
+    /// Returns a description of the alert, such as `close notify`.
+    ///
+    /// This corresponds to [`SSL_alert_desc_string_long`].
+    ///
+    /// [`SSL_alert_desc_string_long`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_alert_desc_string_long.html
+    pub fn description(&self) -> &'static str {
+        unsafe {
+            let ptr = ffi::SSL_alert_desc_string_long(self.0);
+            str::from_utf8(CStr::from_ptr(ptr as *const _).to_bytes()).unwrap()
+        }
+    }
 }
 
 /// An error returned from an ALPN selection callback.
@@ -541,6 +625,62 @@ impl ClientHelloResponse {
     pub const RETRY: ClientHelloResponse = ClientHelloResponse(ffi::SSL_CLIENT_HELLO_RETRY);
 }
 
+/// The result of a certificate callback.
+///
+/// Requires OpenSSL 1.0.2 or newer.
+#[cfg(ossl102)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CertCallbackResponse(c_int);
+
+#[cfg(ossl102)]
+impl CertCallbackResponse {
+    /// Continue the handshake.
+    pub const SUCCESS: CertCallbackResponse = CertCallbackResponse(1);
+
+    /// Return from the handshake with an `ErrorCode::WANT_X509_LOOKUP` error.
+    ///
+    /// The callback will be invoked again when the handshake is resumed.
+    pub const RETRY: CertCallbackResponse = CertCallbackResponse(-1);
+}
+
+/// Whether TLSv1.3 early data was sent and accepted.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslEarlyDataStatus(c_int);
+
+#[cfg(ossl111)]
+impl SslEarlyDataStatus {
+    /// No early data was sent.
+    pub const NOT_SENT: SslEarlyDataStatus = SslEarlyDataStatus(ffi::SSL_EARLY_DATA_NOT_SENT);
+
+    /// Early data was sent but rejected by the server.
+    pub const REJECTED: SslEarlyDataStatus = SslEarlyDataStatus(ffi::SSL_EARLY_DATA_REJECTED);
+
+    /// Early data was sent and accepted by the server.
+    pub const ACCEPTED: SslEarlyDataStatus = SslEarlyDataStatus(ffi::SSL_EARLY_DATA_ACCEPTED);
+}
+
+/// The type of a TLSv1.3 key update.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslKeyUpdateType(c_int);
+
+#[cfg(ossl111)]
+impl SslKeyUpdateType {
+    /// No key update is pending.
+    pub const NONE: SslKeyUpdateType = SslKeyUpdateType(ffi::SSL_KEY_UPDATE_NONE);
+
+    /// Update the sending keys without asking the peer to update its own.
+    pub const NOT_REQUESTED: SslKeyUpdateType = SslKeyUpdateType(ffi::SSL_KEY_UPDATE_NOT_REQUESTED);
+
+    /// Update the sending keys and ask the peer to update its own.
+    pub const REQUESTED: SslKeyUpdateType = SslKeyUpdateType(ffi::SSL_KEY_UPDATE_REQUESTED);
+}
+
 /// An SSL/TLS protocol version.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct SslVersion(c_int);
@@ -977,6 +1117,128 @@ impl SslContextBuilder {
         unsafe { cvt(ffi::SSL_CTX_use_PrivateKey(self.as_ptr(), key.as_ptr())).map(|_| ()) }
     }
 
+    /// Appends a certificate to the chain of the current certificate.
+    ///
+    /// A context can hold one certificate for each key type, such as RSA and ECDSA, and OpenSSL
+    /// selects the one to use for each handshake based on what the client supports. Unlike
+    /// `add_extra_chain_cert`, which adds to a chain shared by all of them, this adds to the chain
+    /// of the certificate most recently set by `set_certificate` or selected by
+    /// `select_current_cert`.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_add1_chain_cert`].
+    ///
+    /// [`SSL_CTX_add1_chain_cert`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_add1_chain_cert.html
+    #[cfg(ossl102)]
+    pub fn add_chain_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_add1_chain_cert(self.as_ptr(), cert.as_ptr()) as c_int).map(|_| ())
+        }
+    }
+
+    /// Removes all certificates from the chain of the current certificate.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_clear_chain_certs`].
+    ///
+    /// [`SSL_CTX_clear_chain_certs`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_clear_chain_certs.html
+    #[cfg(ossl102)]
+    pub fn clear_chain_certs(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_clear_chain_certs(self.as_ptr()) as c_int).map(|_| ()) }
+    }
+
+    /// Makes a previously installed certificate the current certificate.
+    ///
+    /// Subsequent calls to methods such as `add_chain_cert` and `build_cert_chain` apply to it. An
+    /// error is returned if `cert` has not been installed along with its private key.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_select_current_cert`].
+    ///
+    /// [`SSL_CTX_select_current_cert`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_select_current_cert.html
+    #[cfg(ossl102)]
+    pub fn select_current_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_select_current_cert(self.as_ptr(), cert.as_ptr()) as c_int)
+                .map(|_| ())
+        }
+    }
+
+    /// Builds the chain of the current certificate from the certificate store.
+    ///
+    /// The chain replaces any previously configured for the certificate.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_build_cert_chain`].
+    ///
+    /// [`SSL_CTX_build_cert_chain`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_build_cert_chain.html
+    #[cfg(ossl102)]
+    pub fn build_cert_chain(&mut self, flags: SslBuildChainFlags) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_build_cert_chain(self.as_ptr(), flags.bits()) as c_int).map(|_| ())
+        }
+    }
+
+    /// Returns all certificates installed along with a private key, in OpenSSL's internal order.
+    ///
+    /// The current certificate is left unchanged.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_current_cert`] and [`SSL_CTX_get0_chain_certs`].
+    ///
+    /// [`SSL_CTX_set_current_cert`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_current_cert.html
+    /// [`SSL_CTX_get0_chain_certs`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_get0_chain_certs.html
+    #[cfg(ossl102)]
+    pub fn certificates(&mut self) -> Vec<SslCertChain> {
+        unsafe {
+            let ctx = self.as_ptr();
+            let current = ffi::SSL_CTX_get0_certificate(ctx);
+            let key = ffi::SSL_CTX_get0_privatekey(ctx);
+
+            let mut certs = vec![];
+            let mut op = ffi::SSL_CERT_SET_FIRST;
+            while ffi::SSL_CTX_set_current_cert(ctx, op) == 1 {
+                op = ffi::SSL_CERT_SET_NEXT;
+
+                let mut chain = ptr::null_mut();
+                ffi::SSL_CTX_get0_chain_certs(ctx, &mut chain);
+                let chain = if chain.is_null() {
+                    vec![]
+                } else {
+                    StackRef::<X509>::from_ptr(chain)
+                        .iter()
+                        .map(X509Ref::to_owned)
+                        .collect()
+                };
+
+                certs.push(SslCertChain {
+                    certificate: X509Ref::from_ptr(ffi::SSL_CTX_get0_certificate(ctx)).to_owned(),
+                    chain,
+                });
+            }
+
+            // OpenSSL can only select certificates which have a private key, but installing a
+            // certificate or key again also makes it current. The references are held across the
+            // call since OpenSSL frees the old certificate or key before taking the new one.
+            if !current.is_null() && !key.is_null() {
+                ffi::SSL_CTX_select_current_cert(ctx, current);
+            } else if !current.is_null() {
+                let current = X509Ref::from_ptr(current).to_owned();
+                let _ = cvt(ffi::SSL_CTX_use_certificate(ctx, current.as_ptr()));
+            } else if !key.is_null() {
+                let key = PKeyRef::<Private>::from_ptr(key).to_owned();
+                let _ = cvt(ffi::SSL_CTX_use_PrivateKey(ctx, key.as_ptr()));
+            }
+
+            certs
+        }
+    }
+
     /// Sets the list of supported ciphers for protocols before TLSv1.3.
     ///
     /// The `set_ciphersuites` method controls the cipher suites for TLSv1.3.
@@ -1020,6 +1282,65 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets the list of supported key exchange groups.
+    ///
+    /// The format consists of group names such as `X25519`, `P-256`, or `ffdhe2048` separated by
+    /// `:` characters in order of preference. Which groups are available depends on the OpenSSL
+    /// version.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set1_groups_list`].
+    ///
+    /// [`SSL_CTX_set1_groups_list`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set1_groups_list.html
+    #[cfg(ossl102)]
+    pub fn set_groups_list(&mut self, groups: &str) -> Result<(), ErrorStack> {
+        let groups = CString::new(groups).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_set1_groups_list(self.as_ptr(), groups.as_ptr()) as c_int)
+                .map(|_| ())
+        }
+    }
+
+    /// Sets the list of supported signature algorithms.
+    ///
+    /// The format consists of algorithms such as `RSA-PSS+SHA256`, `ECDSA+SHA384`, or
+    /// `ed25519` separated by `:` characters in order of preference.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set1_sigalgs_list`].
+    ///
+    /// [`SSL_CTX_set1_sigalgs_list`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set1_sigalgs_list.html
+    #[cfg(ossl102)]
+    pub fn set_sigalgs_list(&mut self, sigalgs: &str) -> Result<(), ErrorStack> {
+        let sigalgs = CString::new(sigalgs).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_set1_sigalgs_list(self.as_ptr(), sigalgs.as_ptr()) as c_int)
+                .map(|_| ())
+        }
+    }
+
+    /// Sets the list of signature algorithms supported for client certificates.
+    ///
+    /// On a server, this is the list sent in certificate requests. On a client, it limits the
+    /// algorithms used to sign with the client certificate. The format is the same as for
+    /// `set_sigalgs_list`, which is used if this list is not set.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set1_client_sigalgs_list`].
+    ///
+    /// [`SSL_CTX_set1_client_sigalgs_list`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set1_client_sigalgs_list.html
+    #[cfg(ossl102)]
+    pub fn set_client_sigalgs_list(&mut self, sigalgs: &str) -> Result<(), ErrorStack> {
+        let sigalgs = CString::new(sigalgs).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_set1_client_sigalgs_list(self.as_ptr(), sigalgs.as_ptr()) as c_int)
+                .map(|_| ())
+        }
+    }
+
     /// Enables ECDHE key exchange with an automatically chosen curve list.
     ///
     /// Requires OpenSSL 1.0.2.
@@ -1286,6 +1607,44 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets the callback used to select the keys which encrypt and decrypt session tickets.
+    ///
+    /// The callback is invoked with `TicketKeyRequest::Encrypt` when the server issues a ticket,
+    /// and with `TicketKeyRequest::Decrypt` and the key name stored in the ticket when a client
+    /// presents one. By default, each context uses its own randomly generated keys, so tickets
+    /// can only be used to resume sessions with the context which issued them.
+    ///
+    /// This corresponds to [`SSL_CTX_set_tlsext_ticket_key_cb`].
+    ///
+    /// [`SSL_CTX_set_tlsext_ticket_key_cb`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_tlsext_ticket_key_cb.html
+    pub fn set_ticket_key_callback<F>(&mut self, callback: F) -> Result<(), ErrorStack>
+    where
+        F: Fn(&mut SslRef, TicketKeyRequest) -> Result<TicketKeyResponse, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            cvt(
+                ffi::SSL_CTX_set_tlsext_ticket_key_cb(self.as_ptr(), Some(raw_ticket_key::<F>))
+                    as c_int,
+            ).map(|_| ())
+        }
+    }
+
+    /// Encrypts and decrypts session tickets with the keys of a `SessionTicketKeys` ring.
+    ///
+    /// Contexts sharing the same keys can resume each other's sessions. The ring can be rotated
+    /// after the context is built through a clone of it.
+    ///
+    /// This corresponds to [`SSL_CTX_set_tlsext_ticket_key_cb`].
+    ///
+    /// [`SSL_CTX_set_tlsext_ticket_key_cb`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_tlsext_ticket_key_cb.html
+    pub fn set_session_ticket_keys(&mut self, keys: SessionTicketKeys) -> Result<(), ErrorStack> {
+        self.set_ticket_key_callback(move |_, request| Ok(keys.lookup(request)))
+    }
+
     /// Sets the callback for providing an identity and pre-shared key for a TLS-PSK client.
     ///
     /// The callback will be called with the SSL context, an identity hint if one was provided
@@ -1347,6 +1706,79 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets the identity hint sent by a TLS-PSK server before TLSv1.3.
+    ///
+    /// The hint is passed to the client's PSK callback. It is not used by TLSv1.3.
+    ///
+    /// This corresponds to [`SSL_CTX_use_psk_identity_hint`].
+    ///
+    /// [`SSL_CTX_use_psk_identity_hint`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_use_psk_identity_hint.html
+    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
+    pub fn use_psk_identity_hint(&mut self, hint: &str) -> Result<(), ErrorStack> {
+        let hint = CString::new(hint).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_use_psk_identity_hint(
+                self.as_ptr(),
+                hint.as_ptr() as *const _,
+            )).map(|_| ())
+        }
+    }
+
+    /// Sets the callback for providing a TLSv1.3 pre-shared key to a client.
+    ///
+    /// The callback is passed the handshake digest of the cipher already chosen for the
+    /// connection, if any, and should return the identity of the pre-shared key along with a
+    /// session describing it, or `None` to not use a pre-shared key. When a digest is given, the
+    /// session's cipher must use that digest. The session can be one previously received from the
+    /// server, or one created with `SslSessionBuilder::new` for an external pre-shared key.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_psk_use_session_callback`].
+    ///
+    /// [`SSL_CTX_set_psk_use_session_callback`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_psk_use_session_callback.html
+    #[cfg(ossl111)]
+    pub fn set_psk_use_session_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, Option<MessageDigest>) -> Result<Option<(Vec<u8>, SslSession)>, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_psk_use_session_callback(
+                self.as_ptr(),
+                Some(callbacks::raw_psk_use_session::<F>),
+            );
+        }
+    }
+
+    /// Sets the callback for looking up a TLSv1.3 pre-shared key on a server.
+    ///
+    /// The callback is passed the identity sent by the client, and should return a session
+    /// describing the matching pre-shared key, or `None` if the identity is unknown. For an
+    /// external pre-shared key, the session can be created with `SslSessionBuilder::new`.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_psk_find_session_callback`].
+    ///
+    /// [`SSL_CTX_set_psk_find_session_callback`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_psk_find_session_callback.html
+    #[cfg(ossl111)]
+    pub fn set_psk_find_session_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &[u8]) -> Result<Option<SslSession>, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_psk_find_session_callback(
+                self.as_ptr(),
+                Some(callbacks::raw_psk_find_session::<F>),
+            );
+        }
+    }
+
     /// Sets the callback which is called when new sessions are negotiated.
     ///
     /// This can be used by clients to implement session caching. While in TLSv1.2 the session is
@@ -1415,6 +1847,41 @@ impl SslContextBuilder {
         ffi::SSL_CTX_sess_set_get_cb(self.as_ptr(), Some(callbacks::raw_get_session::<F>));
     }
 
+    /// Stores server-side sessions in an external cache rather than OpenSSL's internal one.
+    ///
+    /// This enables server-side session caching and installs new, remove, and get session
+    /// callbacks which store sessions in `cache`. Sessions are serialized to DER, so sessions
+    /// stored by one context can be resumed by another sharing the same cache. Those contexts
+    /// should have the same session ID context configured.
+    ///
+    /// Note that the cache is only used for stateful resumption. Clients resuming with session
+    /// tickets do not consult it, which can be prevented with `SslOptions::NO_TICKET`.
+    pub fn set_session_cache<C>(&mut self, cache: C)
+    where
+        C: SessionCache,
+    {
+        let cache = Arc::new(cache);
+
+        self.set_session_cache_mode(SslSessionCacheMode::SERVER | SslSessionCacheMode::NO_INTERNAL);
+
+        let put = cache.clone();
+        self.set_new_session_callback(move |_, session| {
+            if let Ok(der) = session.to_der() {
+                put.put(session.id(), der, session_timeout(&session));
+            }
+        });
+
+        let remove = cache.clone();
+        self.set_remove_session_callback(move |_, session| remove.remove(session.id()));
+
+        // deserialized sessions are not associated with any context
+        unsafe {
+            self.set_get_session_callback(move |_, id| {
+                cache.get(id).and_then(|der| SslSession::from_der(&der).ok())
+            });
+        }
+    }
+
     /// Sets the TLS key logging callback.
     ///
     /// The callback is invoked whenever TLS key material is generated, and is passed a line of NSS
@@ -1437,6 +1904,46 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets a callback which is called with each protocol message sent or received.
+    ///
+    /// This is intended for debugging. The `log_message` function, available with the `log`
+    /// feature, can be used as the callback to log messages in a decoded form.
+    ///
+    /// The callback only applies to `Ssl`s created after it is set.
+    ///
+    /// This corresponds to [`SSL_CTX_set_msg_callback`].
+    ///
+    /// [`SSL_CTX_set_msg_callback`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_msg_callback.html
+    pub fn set_msg_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, SslMessage) + 'static + Sync + Send,
+    {
+        unsafe {
+            let ptr = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_msg_callback(self.as_ptr(), Some(callbacks::raw_msg::<F>));
+            ffi::SSL_CTX_set_msg_callback_arg(self.as_ptr(), ptr);
+        }
+    }
+
+    /// Sets a callback which is called as the handshake progresses and when alerts are sent or
+    /// received.
+    ///
+    /// The `log_info` function, available with the `log` feature, can be used as the callback to
+    /// log these events.
+    ///
+    /// This corresponds to [`SSL_CTX_set_info_callback`].
+    ///
+    /// [`SSL_CTX_set_info_callback`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_info_callback.html
+    pub fn set_info_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, SslInfoEvent) + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_info_callback(self.as_ptr(), Some(callbacks::raw_info::<F>));
+        }
+    }
+
     /// Sets the session caching mode use for connections made with the context.
     ///
     /// Returns the previous session caching mode.
@@ -1451,6 +1958,30 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets the maximum number of sessions held in the context's internal session cache.
+    ///
+    /// A size of 0 places no limit on the cache. Defaults to 20480.
+    ///
+    /// Returns the previous size.
+    ///
+    /// This corresponds to [`SSL_CTX_sess_set_cache_size`].
+    ///
+    /// [`SSL_CTX_sess_set_cache_size`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_sess_set_cache_size.html
+    pub fn set_session_cache_size(&mut self, size: u32) -> u32 {
+        unsafe { ffi::SSL_CTX_sess_set_cache_size(self.as_ptr(), size as c_long) as u32 }
+    }
+
+    /// Sets the lifetime, in seconds, of sessions established with the context.
+    ///
+    /// Returns the previous timeout.
+    ///
+    /// This corresponds to [`SSL_CTX_set_timeout`].
+    ///
+    /// [`SSL_CTX_set_timeout`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_timeout.html
+    pub fn set_session_timeout(&mut self, timeout: u32) -> u32 {
+        unsafe { ffi::SSL_CTX_set_timeout(self.as_ptr(), timeout as c_long) as u32 }
+    }
+
     /// Sets the callback for generating an application cookie for TLS1.3
     /// stateless handshakes.
     ///
@@ -1616,6 +2147,21 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets whether clients will send a certificate if the server requests one after the
+    /// handshake.
+    ///
+    /// It is disabled by default.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_post_handshake_auth`].
+    ///
+    /// [`SSL_CTX_set_post_handshake_auth`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_post_handshake_auth.html
+    #[cfg(ossl111)]
+    pub fn set_post_handshake_auth(&mut self, enabled: bool) {
+        unsafe { ffi::SSL_CTX_set_post_handshake_auth(self.as_ptr(), enabled as c_int) }
+    }
+
     /// Sets a callback which will be invoked just after the client's hello message is received.
     /// 
     /// Requires OpenSSL 1.1.1 or newer.
@@ -1641,8 +2187,52 @@ impl SslContextBuilder {
         }
     }
 
-    /// Consumes the builder, returning a new `SslContext`.
-    pub fn build(self) -> SslContext {
+    /// Sets a callback which is invoked to select the certificate used for a connection.
+    ///
+    /// On the server side, the callback is invoked once the client's hello message has been
+    /// processed, so the server name, the client's signature algorithms, and its cipher list are
+    /// available. On the client side, it is invoked when the server requests a certificate. The
+    /// callback can configure the connection's certificate with `SslRef::set_certificate`,
+    /// `SslRef::set_private_key`, and `SslRef::add_chain_cert`, or return
+    /// `CertCallbackResponse::RETRY` to pause the handshake while the certificate is looked up.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_cert_cb`].
+    ///
+    /// [`SSL_CTX_set_cert_cb`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_cert_cb.html
+    #[cfg(ossl102)]
+    pub fn set_cert_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Result<CertCallbackResponse, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            let ptr = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_cert_cb(self.as_ptr(), Some(callbacks::raw_cert::<F>), ptr);
+        }
+    }
+
+    /// Sets a callback which is invoked to supply a client certificate when the server requests
+    /// one.
+    ///
+    /// The callback is only invoked if no certificate has been configured for the connection. It
+    /// returns the certificate and its private key, or `None` to continue without one.
+    ///
+    /// This corresponds to [`SSL_CTX_set_client_cert_cb`].
+    ///
+    /// [`SSL_CTX_set_client_cert_cb`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_client_cert_cb.html
+    pub fn set_client_cert_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Option<(X509, PKey<Private>)> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_client_cert_cb(self.as_ptr(), Some(callbacks::raw_client_cert::<F>));
+        }
+    }
+
+    /// Consumes the builder, returning a new `SslContext`.
+    pub fn build(self) -> SslContext {
         self.0
     }
 }
@@ -1807,6 +2397,143 @@ impl SslContextRef {
     pub fn max_early_data(&self) -> u32 {
         unsafe { ffi::SSL_CTX_get_max_early_data(self.as_ptr()) }
     }
+
+    /// Returns the maximum number of sessions held in the context's internal session cache.
+    ///
+    /// This corresponds to [`SSL_CTX_sess_get_cache_size`].
+    ///
+    /// [`SSL_CTX_sess_get_cache_size`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_sess_set_cache_size.html
+    pub fn session_cache_size(&self) -> u32 {
+        unsafe { ffi::SSL_CTX_sess_get_cache_size(self.as_ptr()) as u32 }
+    }
+
+    /// Returns the lifetime, in seconds, of sessions established with the context.
+    ///
+    /// This corresponds to [`SSL_CTX_get_timeout`].
+    ///
+    /// [`SSL_CTX_get_timeout`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_timeout.html
+    pub fn session_timeout(&self) -> u32 {
+        unsafe { ffi::SSL_CTX_get_timeout(self.as_ptr()) as u32 }
+    }
+
+    /// Removes sessions which have expired as of `time` from the internal session cache.
+    ///
+    /// `time` is in seconds since the Unix epoch. Expired sessions are otherwise only removed as
+    /// they are looked up, or periodically as new sessions are added.
+    ///
+    /// This corresponds to [`SSL_CTX_flush_sessions`].
+    ///
+    /// [`SSL_CTX_flush_sessions`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_flush_sessions.html
+    pub fn flush_sessions(&self, time: i64) {
+        unsafe { ffi::SSL_CTX_flush_sessions(self.as_ptr(), time as c_long) }
+    }
+
+    /// Returns statistics about the context's session cache and handshakes.
+    ///
+    /// This corresponds to [`SSL_CTX_sess_number`] and the related counters.
+    ///
+    /// [`SSL_CTX_sess_number`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_sess_number.html
+    pub fn session_cache_stats(&self) -> SslSessionCacheStats {
+        unsafe {
+            let ctx = self.as_ptr();
+            SslSessionCacheStats {
+                number: ffi::SSL_CTX_sess_number(ctx) as u64,
+                connect: ffi::SSL_CTX_sess_connect(ctx) as u64,
+                connect_good: ffi::SSL_CTX_sess_connect_good(ctx) as u64,
+                connect_renegotiate: ffi::SSL_CTX_sess_connect_renegotiate(ctx) as u64,
+                accept: ffi::SSL_CTX_sess_accept(ctx) as u64,
+                accept_good: ffi::SSL_CTX_sess_accept_good(ctx) as u64,
+                accept_renegotiate: ffi::SSL_CTX_sess_accept_renegotiate(ctx) as u64,
+                hits: ffi::SSL_CTX_sess_hits(ctx) as u64,
+                cb_hits: ffi::SSL_CTX_sess_cb_hits(ctx) as u64,
+                misses: ffi::SSL_CTX_sess_misses(ctx) as u64,
+                timeouts: ffi::SSL_CTX_sess_timeouts(ctx) as u64,
+                cache_full: ffi::SSL_CTX_sess_cache_full(ctx) as u64,
+            }
+        }
+    }
+}
+
+/// Statistics about a context's session cache and handshakes.
+///
+/// The handshake counters include both full and resumed handshakes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslSessionCacheStats {
+    number: u64,
+    connect: u64,
+    connect_good: u64,
+    connect_renegotiate: u64,
+    accept: u64,
+    accept_good: u64,
+    accept_renegotiate: u64,
+    hits: u64,
+    cb_hits: u64,
+    misses: u64,
+    timeouts: u64,
+    cache_full: u64,
+}
+
+impl SslSessionCacheStats {
+    /// Returns the number of sessions currently in the internal session cache.
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// Returns the number of client handshakes started.
+    pub fn connect(&self) -> u64 {
+        self.connect
+    }
+
+    /// Returns the number of client handshakes completed successfully.
+    pub fn connect_good(&self) -> u64 {
+        self.connect_good
+    }
+
+    /// Returns the number of client renegotiations started.
+    pub fn connect_renegotiate(&self) -> u64 {
+        self.connect_renegotiate
+    }
+
+    /// Returns the number of server handshakes started.
+    pub fn accept(&self) -> u64 {
+        self.accept
+    }
+
+    /// Returns the number of server handshakes completed successfully.
+    pub fn accept_good(&self) -> u64 {
+        self.accept_good
+    }
+
+    /// Returns the number of server renegotiations started.
+    pub fn accept_renegotiate(&self) -> u64 {
+        self.accept_renegotiate
+    }
+
+    /// Returns the number of sessions successfully resumed.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns the number of sessions retrieved from an external cache by the get session callback.
+    pub fn cb_hits(&self) -> u64 {
+        self.cb_hits
+    }
+
+    /// Returns the number of sessions proposed by clients which were not found in the internal
+    /// cache.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Returns the number of sessions proposed by clients which were found but had expired.
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts
+    }
+
+    /// Returns the number of sessions removed because the cache was full.
+    pub fn cache_full(&self) -> u64 {
+        self.cache_full
+    }
 }
 
 /// Information about the state of a cipher.
@@ -1859,6 +2586,10 @@ impl ForeignTypeRef for SslCipherRef {
     type CType = ffi::SSL_CIPHER;
 }
 
+impl Stackable for SslCipher {
+    type StackType = ffi::stack_st_SSL_CIPHER;
+}
+
 impl SslCipherRef {
     /// Returns the name of the cipher.
     ///
@@ -1874,6 +2605,25 @@ impl SslCipherRef {
         str::from_utf8(name.to_bytes()).unwrap()
     }
 
+    /// Returns the RFC-standard name of the cipher, if one exists.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CIPHER_standard_name`].
+    ///
+    /// [`SSL_CIPHER_standard_name`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CIPHER_standard_name.html
+    #[cfg(ossl111)]
+    pub fn standard_name(&self) -> Option<&'static str> {
+        unsafe {
+            let ptr = ffi::SSL_CIPHER_standard_name(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(str::from_utf8(CStr::from_ptr(ptr as *const _).to_bytes()).unwrap())
+            }
+        }
+    }
+
     /// Returns the SSL/TLS protocol version that first defined the cipher.
     ///
     /// This corresponds to [`SSL_CIPHER_get_version`].
@@ -1955,6 +2705,109 @@ impl SslCipherRef {
     }
 }
 
+/// A signature algorithm advertised by the peer.
+///
+/// Requires OpenSSL 1.0.2 or newer.
+#[cfg(ossl102)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslSignatureAlgorithm {
+    code: u16,
+    signature: Nid,
+    digest: Nid,
+}
+
+#[cfg(ossl102)]
+impl SslSignatureAlgorithm {
+    /// Returns the algorithm's TLS code point, such as `0x0804` for `rsa_pss_rsae_sha256`.
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+
+    /// Returns the NID of the public key algorithm used to sign.
+    pub fn signature(&self) -> Nid {
+        self.signature
+    }
+
+    /// Returns the NID of the digest, or `Nid::UNDEF` if the algorithm does not use a separate
+    /// digest.
+    pub fn digest(&self) -> Nid {
+        self.digest
+    }
+}
+
+#[cfg(ossl102)]
+unsafe fn signature_algorithms(
+    ssl: *mut ffi::SSL,
+    get: unsafe extern "C" fn(
+        *mut ffi::SSL,
+        c_int,
+        *mut c_int,
+        *mut c_int,
+        *mut c_int,
+        *mut c_uchar,
+        *mut c_uchar,
+    ) -> c_int,
+) -> Vec<SslSignatureAlgorithm> {
+    // SSL_get_shared_sigalgs rejects negative indices, but both functions return the count
+    let count = get(
+        ssl,
+        0,
+        ptr::null_mut(),
+        ptr::null_mut(),
+        ptr::null_mut(),
+        ptr::null_mut(),
+        ptr::null_mut(),
+    );
+
+    let mut algs = vec![];
+    for idx in 0..count {
+        let mut signature = 0;
+        let mut digest = 0;
+        let mut rsig = 0;
+        let mut rhash = 0;
+        get(
+            ssl,
+            idx,
+            &mut signature,
+            &mut digest,
+            ptr::null_mut(),
+            &mut rsig,
+            &mut rhash,
+        );
+        algs.push(SslSignatureAlgorithm {
+            code: (u16::from(rhash) << 8) | u16::from(rsig),
+            signature: Nid::from_raw(signature),
+            digest: Nid::from_raw(digest),
+        });
+    }
+    algs
+}
+
+/// A certificate installed in a context, along with its chain.
+///
+/// Requires OpenSSL 1.0.2 or newer.
+#[cfg(ossl102)]
+#[derive(Clone)]
+pub struct SslCertChain {
+    certificate: X509,
+    chain: Vec<X509>,
+}
+
+#[cfg(ossl102)]
+impl SslCertChain {
+    /// Returns the leaf certificate.
+    pub fn certificate(&self) -> &X509Ref {
+        &self.certificate
+    }
+
+    /// Returns the certificate's chain, not including the leaf certificate itself.
+    ///
+    /// This does not include certificates added with `SslContextBuilder::add_extra_chain_cert`.
+    pub fn chain(&self) -> &[X509] {
+        &self.chain
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::SSL_SESSION;
     fn drop = ffi::SSL_SESSION_free;
@@ -2046,135 +2899,427 @@ impl SslSessionRef {
         unsafe { ffi::SSL_SESSION_get_max_early_data(self.as_ptr()) }
     }
 
-    to_der! {
-        /// Serializes the session into a DER-encoded structure.
-        ///
-        /// This corresponds to [`i2d_SSL_SESSION`].
-        ///
-        /// [`i2d_SSL_SESSION`]: https://www.openssl.org/docs/man1.0.2/ssl/i2d_SSL_SESSION.html
-        to_der,
-        ffi::i2d_SSL_SESSION
+    /// Returns the protocol version negotiated for the session.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_get_protocol_version`].
+    ///
+    /// [`SSL_SESSION_get_protocol_version`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_SESSION_get_protocol_version.html
+    #[cfg(ossl110)]
+    pub fn protocol_version(&self) -> SslVersion {
+        unsafe { SslVersion(ffi::SSL_SESSION_get_protocol_version(self.as_ptr())) }
     }
-}
-
-foreign_type_and_impl_send_sync! {
-    type CType = ffi::SSL;
-    fn drop = ffi::SSL_free;
 
-    /// The state of an SSL/TLS session.
+    /// Returns the cipher negotiated for the session.
     ///
-    /// `Ssl` objects are created from an [`SslContext`], which provides configuration defaults.
-    /// These defaults can be overridden on a per-`Ssl` basis, however.
+    /// Requires OpenSSL 1.1.0 or newer.
     ///
-    /// [`SslContext`]: struct.SslContext.html
-    pub struct Ssl;
-
-    /// Reference to an [`Ssl`].
+    /// This corresponds to [`SSL_SESSION_get0_cipher`].
     ///
-    /// [`Ssl`]: struct.Ssl.html
-    pub struct SslRef;
-}
-
-impl fmt::Debug for Ssl {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(&**self, fmt)
+    /// [`SSL_SESSION_get0_cipher`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_get0_cipher.html
+    #[cfg(ossl110)]
+    pub fn cipher(&self) -> Option<&SslCipherRef> {
+        unsafe {
+            let ptr = ffi::SSL_SESSION_get0_cipher(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(SslCipherRef::from_ptr(ptr as *mut _))
+            }
+        }
     }
-}
 
-impl Ssl {
-    /// Returns a new extra data index.
+    /// Returns the peer's certificate, if present.
     ///
-    /// Each invocation of this function is guaranteed to return a distinct index. These can be used
-    /// to store data in the context that can be retrieved later by callbacks, for example.
+    /// Requires OpenSSL 1.1.0 or newer.
     ///
-    /// This corresponds to [`SSL_get_ex_new_index`].
+    /// This corresponds to [`SSL_SESSION_get0_peer`].
     ///
-    /// [`SSL_get_ex_new_index`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_get_ex_new_index.html
-    pub fn new_ex_index<T>() -> Result<Index<Ssl, T>, ErrorStack>
-    where
-        T: 'static + Sync + Send,
-    {
+    /// [`SSL_SESSION_get0_peer`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_SESSION_get0_peer.html
+    #[cfg(ossl110)]
+    pub fn peer_certificate(&self) -> Option<&X509Ref> {
         unsafe {
-            ffi::init();
-            let idx = cvt_n(get_new_ssl_idx(free_data_box::<T>))?;
-            Ok(Index::from_raw(idx))
+            let ptr = ffi::SSL_SESSION_get0_peer(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(X509Ref::from_ptr(ptr))
+            }
         }
     }
 
-    // FIXME should return a result?
-    fn cached_ex_index<T>() -> Index<Ssl, T>
-    where
-        T: 'static + Sync + Send,
-    {
+    /// Returns the server name sent by the client when the session was established, if any.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_get0_hostname`].
+    ///
+    /// [`SSL_SESSION_get0_hostname`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_get0_hostname.html
+    #[cfg(ossl111)]
+    pub fn hostname(&self) -> Option<&str> {
         unsafe {
-            let idx = *SSL_INDEXES
-                .lock()
-                .unwrap_or_else(|e| e.into_inner())
-                .entry(TypeId::of::<T>())
-                .or_insert_with(|| Ssl::new_ex_index::<T>().unwrap().as_raw());
-            Index::from_raw(idx)
+            let ptr = ffi::SSL_SESSION_get0_hostname(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                str::from_utf8(CStr::from_ptr(ptr).to_bytes()).ok()
+            }
         }
     }
 
-    /// Creates a new `Ssl`.
+    /// Returns the ALPN protocol selected when the session was established, if any.
     ///
-    /// This corresponds to [`SSL_new`].
+    /// Requires OpenSSL 1.1.1 or newer.
     ///
-    /// [`SSL_new`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_new.html
-    pub fn new(ctx: &SslContext) -> Result<Ssl, ErrorStack> {
+    /// This corresponds to [`SSL_SESSION_get0_alpn_selected`].
+    ///
+    /// [`SSL_SESSION_get0_alpn_selected`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_get0_alpn_selected.html
+    #[cfg(ossl111)]
+    pub fn alpn_selected(&self) -> Option<&[u8]> {
         unsafe {
-            let ssl = cvt_p(ffi::SSL_new(ctx.as_ptr()))?;
-            Ok(Ssl::from_ptr(ssl))
+            let mut data = ptr::null();
+            let mut len = 0;
+            ffi::SSL_SESSION_get0_alpn_selected(self.as_ptr(), &mut data, &mut len);
+            if data.is_null() || len == 0 {
+                None
+            } else {
+                Some(slice::from_raw_parts(data, len))
+            }
         }
     }
 
-    /// Initiates a client-side TLS handshake.
+    /// Returns the time at which the session was established, in seconds since the Unix epoch.
     ///
-    /// This corresponds to [`SSL_connect`].
+    /// This corresponds to [`SSL_SESSION_get_time`].
     ///
-    /// # Warning
+    /// [`SSL_SESSION_get_time`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_get_time.html
+    pub fn time(&self) -> i64 {
+        unsafe { ffi::SSL_SESSION_get_time(self.as_ptr()) as i64 }
+    }
+
+    /// Returns the lifetime of the session, in seconds.
     ///
-    /// OpenSSL's default configuration is insecure. It is highly recommended to use
-    /// `SslConnector` rather than `Ssl` directly, as it manages that configuration.
+    /// This corresponds to [`SSL_SESSION_get_timeout`].
     ///
-    /// [`SSL_connect`]: https://www.openssl.org/docs/manmaster/man3/SSL_connect.html
-    pub fn connect<S>(self, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
-    where
-        S: Read + Write,
-    {
-        SslStreamBuilder::new(self, stream).connect()
+    /// [`SSL_SESSION_get_timeout`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_get_time.html
+    pub fn timeout(&self) -> u32 {
+        unsafe { ffi::SSL_SESSION_get_timeout(self.as_ptr()) as u32 }
     }
 
-    /// Initiates a server-side TLS handshake.
-    ///
-    /// This corresponds to [`SSL_accept`].
+    /// Returns the lifetime hint of the session ticket sent by the server, in seconds.
     ///
-    /// # Warning
+    /// Requires OpenSSL 1.1.0 or newer.
     ///
-    /// OpenSSL's default configuration is insecure. It is highly recommended to use
-    /// `SslAcceptor` rather than `Ssl` directly, as it manages that configuration.
+    /// This corresponds to [`SSL_SESSION_get_ticket_lifetime_hint`].
     ///
-    /// [`SSL_accept`]: https://www.openssl.org/docs/manmaster/man3/SSL_accept.html
-    pub fn accept<S>(self, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
-    where
-        S: Read + Write,
-    {
-        SslStreamBuilder::new(self, stream).accept()
+    /// [`SSL_SESSION_get_ticket_lifetime_hint`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_SESSION_get_ticket_lifetime_hint.html
+    #[cfg(ossl110)]
+    pub fn ticket_lifetime_hint(&self) -> u32 {
+        unsafe { ffi::SSL_SESSION_get_ticket_lifetime_hint(self.as_ptr()) as u32 }
     }
-}
 
-impl fmt::Debug for SslRef {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        fmt.debug_struct("Ssl")
-            .field("state", &self.state_string_long())
-            .field("verify_result", &self.verify_result())
-            .finish()
+    /// Determines if the session contains a session ticket.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_has_ticket`].
+    ///
+    /// [`SSL_SESSION_has_ticket`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_SESSION_has_ticket.html
+    #[cfg(ossl110)]
+    pub fn has_ticket(&self) -> bool {
+        unsafe { ffi::SSL_SESSION_has_ticket(self.as_ptr()) != 0 }
     }
-}
 
-impl SslRef {
-    fn get_raw_rbio(&self) -> *mut ffi::BIO {
-        unsafe { ffi::SSL_get_rbio(self.as_ptr()) }
+    /// Determines if the session can be used to resume a connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_is_resumable`].
+    ///
+    /// [`SSL_SESSION_is_resumable`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_is_resumable.html
+    #[cfg(ossl111)]
+    pub fn is_resumable(&self) -> bool {
+        unsafe { ffi::SSL_SESSION_is_resumable(self.as_ptr()) != 0 }
+    }
+
+    /// Returns a human-readable description of the session.
+    ///
+    /// This corresponds to [`SSL_SESSION_print`].
+    ///
+    /// [`SSL_SESSION_print`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_print.html
+    pub fn to_text(&self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let bio = MemBio::new()?;
+            cvt(ffi::SSL_SESSION_print(bio.as_ptr(), self.as_ptr()))?;
+            Ok(bio.get_buf().to_owned())
+        }
+    }
+
+    to_der! {
+        /// Serializes the session into a DER-encoded structure.
+        ///
+        /// This corresponds to [`i2d_SSL_SESSION`].
+        ///
+        /// [`i2d_SSL_SESSION`]: https://www.openssl.org/docs/man1.0.2/ssl/i2d_SSL_SESSION.html
+        to_der,
+        ffi::i2d_SSL_SESSION
+    }
+}
+
+/// A builder for `SslSession`s.
+///
+/// Sessions are reference counted and shared with OpenSSL's session cache, so they are modified
+/// through a copy rather than in place.
+pub struct SslSessionBuilder(SslSession);
+
+impl SslSessionBuilder {
+    /// Creates a new builder for an empty session.
+    ///
+    /// This can be used to construct a TLSv1.3 external pre-shared key from its key, cipher, and
+    /// protocol version.
+    ///
+    /// This corresponds to [`SSL_SESSION_new`].
+    ///
+    /// [`SSL_SESSION_new`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_new.html
+    pub fn new() -> Result<SslSessionBuilder, ErrorStack> {
+        unsafe { cvt_p(ffi::SSL_SESSION_new()).map(|p| SslSessionBuilder(SslSession(p))) }
+    }
+
+    /// Creates a new builder initialized with a copy of an existing session.
+    ///
+    /// This corresponds to [`SSL_SESSION_dup`] on OpenSSL 1.1.1, and serializes and deserializes
+    /// the session otherwise.
+    ///
+    /// [`SSL_SESSION_dup`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_dup.html
+    pub fn from_session(session: &SslSessionRef) -> Result<SslSessionBuilder, ErrorStack> {
+        dup_session(session).map(SslSessionBuilder)
+    }
+
+    /// Sets the time at which the session was established, in seconds since the Unix epoch.
+    ///
+    /// This corresponds to [`SSL_SESSION_set_time`].
+    ///
+    /// [`SSL_SESSION_set_time`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_get_time.html
+    pub fn set_time(&mut self, time: i64) {
+        unsafe {
+            ffi::SSL_SESSION_set_time(self.0.as_ptr(), time as c_long);
+        }
+    }
+
+    /// Sets the lifetime of the session, in seconds.
+    ///
+    /// This corresponds to [`SSL_SESSION_set_timeout`].
+    ///
+    /// [`SSL_SESSION_set_timeout`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_get_time.html
+    pub fn set_timeout(&mut self, timeout: u32) {
+        unsafe {
+            ffi::SSL_SESSION_set_timeout(self.0.as_ptr(), timeout as c_long);
+        }
+    }
+
+    /// Sets the session ID context of the session.
+    ///
+    /// A server will only resume a session whose ID context matches its own, as configured with
+    /// `SslContextBuilder::set_session_id_context`.
+    ///
+    /// This corresponds to [`SSL_SESSION_set1_id_context`].
+    ///
+    /// [`SSL_SESSION_set1_id_context`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_set1_id_context.html
+    pub fn set_id_context(&mut self, sid_ctx: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(sid_ctx.len() <= c_uint::max_value() as usize);
+            cvt(ffi::SSL_SESSION_set1_id_context(
+                self.0.as_ptr(),
+                sid_ctx.as_ptr(),
+                sid_ctx.len() as c_uint,
+            )).map(|_| ())
+        }
+    }
+
+    /// Sets the master key of the session.
+    ///
+    /// For a TLSv1.3 external pre-shared key, this is the key itself.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_set1_master_key`].
+    ///
+    /// [`SSL_SESSION_set1_master_key`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_set1_master_key.html
+    #[cfg(ossl110)]
+    pub fn set_master_key(&mut self, key: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_SESSION_set1_master_key(
+                self.0.as_ptr(),
+                key.as_ptr(),
+                key.len(),
+            )).map(|_| ())
+        }
+    }
+
+    /// Sets the cipher associated with the session.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_set_cipher`].
+    ///
+    /// [`SSL_SESSION_set_cipher`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_set_cipher.html
+    #[cfg(ossl111)]
+    pub fn set_cipher(&mut self, cipher: &SslCipherRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_SESSION_set_cipher(self.0.as_ptr(), cipher.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the protocol version associated with the session.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_set_protocol_version`].
+    ///
+    /// [`SSL_SESSION_set_protocol_version`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_set_protocol_version.html
+    #[cfg(ossl111)]
+    pub fn set_protocol_version(&mut self, version: SslVersion) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_SESSION_set_protocol_version(
+                self.0.as_ptr(),
+                version.0,
+            )).map(|_| ())
+        }
+    }
+
+    /// Consumes the builder, returning the session.
+    pub fn build(self) -> SslSession {
+        self.0
+    }
+}
+
+#[cfg(ossl111)]
+fn dup_session(session: &SslSessionRef) -> Result<SslSession, ErrorStack> {
+    unsafe { cvt_p(ffi::SSL_SESSION_dup(session.as_ptr())).map(SslSession) }
+}
+
+#[cfg(not(ossl111))]
+fn dup_session(session: &SslSessionRef) -> Result<SslSession, ErrorStack> {
+    SslSession::from_der(&session.to_der()?)
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::SSL;
+    fn drop = ffi::SSL_free;
+
+    /// The state of an SSL/TLS session.
+    ///
+    /// `Ssl` objects are created from an [`SslContext`], which provides configuration defaults.
+    /// These defaults can be overridden on a per-`Ssl` basis, however.
+    ///
+    /// [`SslContext`]: struct.SslContext.html
+    pub struct Ssl;
+
+    /// Reference to an [`Ssl`].
+    ///
+    /// [`Ssl`]: struct.Ssl.html
+    pub struct SslRef;
+}
+
+impl fmt::Debug for Ssl {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, fmt)
+    }
+}
+
+impl Ssl {
+    /// Returns a new extra data index.
+    ///
+    /// Each invocation of this function is guaranteed to return a distinct index. These can be used
+    /// to store data in the context that can be retrieved later by callbacks, for example.
+    ///
+    /// This corresponds to [`SSL_get_ex_new_index`].
+    ///
+    /// [`SSL_get_ex_new_index`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_get_ex_new_index.html
+    pub fn new_ex_index<T>() -> Result<Index<Ssl, T>, ErrorStack>
+    where
+        T: 'static + Sync + Send,
+    {
+        unsafe {
+            ffi::init();
+            let idx = cvt_n(get_new_ssl_idx(free_data_box::<T>))?;
+            Ok(Index::from_raw(idx))
+        }
+    }
+
+    // FIXME should return a result?
+    fn cached_ex_index<T>() -> Index<Ssl, T>
+    where
+        T: 'static + Sync + Send,
+    {
+        unsafe {
+            let idx = *SSL_INDEXES
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(TypeId::of::<T>())
+                .or_insert_with(|| Ssl::new_ex_index::<T>().unwrap().as_raw());
+            Index::from_raw(idx)
+        }
+    }
+
+    /// Creates a new `Ssl`.
+    ///
+    /// This corresponds to [`SSL_new`].
+    ///
+    /// [`SSL_new`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_new.html
+    pub fn new(ctx: &SslContext) -> Result<Ssl, ErrorStack> {
+        unsafe {
+            let ssl = cvt_p(ffi::SSL_new(ctx.as_ptr()))?;
+            Ok(Ssl::from_ptr(ssl))
+        }
+    }
+
+    /// Initiates a client-side TLS handshake.
+    ///
+    /// This corresponds to [`SSL_connect`].
+    ///
+    /// # Warning
+    ///
+    /// OpenSSL's default configuration is insecure. It is highly recommended to use
+    /// `SslConnector` rather than `Ssl` directly, as it manages that configuration.
+    ///
+    /// [`SSL_connect`]: https://www.openssl.org/docs/manmaster/man3/SSL_connect.html
+    pub fn connect<S>(self, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
+    where
+        S: Read + Write,
+    {
+        SslStreamBuilder::new(self, stream).connect()
+    }
+
+    /// Initiates a server-side TLS handshake.
+    ///
+    /// This corresponds to [`SSL_accept`].
+    ///
+    /// # Warning
+    ///
+    /// OpenSSL's default configuration is insecure. It is highly recommended to use
+    /// `SslAcceptor` rather than `Ssl` directly, as it manages that configuration.
+    ///
+    /// [`SSL_accept`]: https://www.openssl.org/docs/manmaster/man3/SSL_accept.html
+    pub fn accept<S>(self, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
+    where
+        S: Read + Write,
+    {
+        SslStreamBuilder::new(self, stream).accept()
+    }
+}
+
+impl fmt::Debug for SslRef {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Ssl")
+            .field("state", &self.state_string_long())
+            .field("verify_result", &self.verify_result())
+            .finish()
+    }
+}
+
+impl SslRef {
+    fn get_raw_rbio(&self) -> *mut ffi::BIO {
+        unsafe { ffi::SSL_get_rbio(self.as_ptr()) }
     }
 
     fn read(&mut self, buf: &mut [u8]) -> c_int {
@@ -2218,6 +3363,194 @@ impl SslRef {
         }
     }
 
+    /// Like [`SslContextBuilder::set_verify_depth`].
+    ///
+    /// This corresponds to [`SSL_set_verify_depth`].
+    ///
+    /// [`SslContextBuilder::set_verify_depth`]: struct.SslContextBuilder.html#method.set_verify_depth
+    /// [`SSL_set_verify_depth`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_CTX_set_verify_depth.html
+    pub fn set_verify_depth(&mut self, depth: u32) {
+        unsafe {
+            ffi::SSL_set_verify_depth(self.as_ptr(), depth as c_int);
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_verify_cert_store`].
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_set0_verify_cert_store`].
+    ///
+    /// [`SslContextBuilder::set_verify_cert_store`]: struct.SslContextBuilder.html#method.set_verify_cert_store
+    /// [`SSL_set0_verify_cert_store`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_CTX_set0_verify_cert_store.html
+    #[cfg(ossl102)]
+    pub fn set_verify_cert_store(&mut self, cert_store: X509Store) -> Result<(), ErrorStack> {
+        unsafe {
+            let ptr = cert_store.as_ptr();
+            cvt(ffi::SSL_set0_verify_cert_store(self.as_ptr(), ptr) as c_int)?;
+            mem::forget(cert_store);
+
+            Ok(())
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_client_ca_list`].
+    ///
+    /// This corresponds to [`SSL_set_client_CA_list`].
+    ///
+    /// [`SslContextBuilder::set_client_ca_list`]: struct.SslContextBuilder.html#method.set_client_ca_list
+    /// [`SSL_set_client_CA_list`]: https://www.openssl.org/docs/manmaster/man3/SSL_CTX_set_client_CA_list.html
+    pub fn set_client_ca_list(&mut self, list: Stack<X509Name>) {
+        unsafe {
+            ffi::SSL_set_client_CA_list(self.as_ptr(), list.as_ptr());
+            mem::forget(list);
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_cipher_list`].
+    ///
+    /// This corresponds to [`SSL_set_cipher_list`].
+    ///
+    /// [`SslContextBuilder::set_cipher_list`]: struct.SslContextBuilder.html#method.set_cipher_list
+    /// [`SSL_set_cipher_list`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_CTX_set_cipher_list.html
+    pub fn set_cipher_list(&mut self, cipher_list: &str) -> Result<(), ErrorStack> {
+        let cipher_list = CString::new(cipher_list).unwrap();
+        unsafe {
+            cvt(ffi::SSL_set_cipher_list(
+                self.as_ptr(),
+                cipher_list.as_ptr() as *const _,
+            )).map(|_| ())
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_ciphersuites`].
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_set_ciphersuites`].
+    ///
+    /// [`SslContextBuilder::set_ciphersuites`]: struct.SslContextBuilder.html#method.set_ciphersuites
+    /// [`SSL_set_ciphersuites`]: https://www.openssl.org/docs/manmaster/man3/SSL_CTX_set_ciphersuites.html
+    #[cfg(ossl111)]
+    pub fn set_ciphersuites(&mut self, cipher_list: &str) -> Result<(), ErrorStack> {
+        let cipher_list = CString::new(cipher_list).unwrap();
+        unsafe {
+            cvt(ffi::SSL_set_ciphersuites(
+                self.as_ptr(),
+                cipher_list.as_ptr() as *const _,
+            )).map(|_| ())
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_options`].
+    ///
+    /// This corresponds to [`SSL_set_options`].
+    ///
+    /// [`SslContextBuilder::set_options`]: struct.SslContextBuilder.html#method.set_options
+    /// [`SSL_set_options`]: https://www.openssl.org/docs/manmaster/man3/SSL_CTX_set_options.html
+    pub fn set_options(&mut self, option: SslOptions) -> SslOptions {
+        let bits = unsafe { ffi::SSL_set_options(self.as_ptr(), option.bits()) };
+        SslOptions { bits }
+    }
+
+    /// Like [`SslContextBuilder::options`].
+    ///
+    /// This corresponds to [`SSL_get_options`].
+    ///
+    /// [`SslContextBuilder::options`]: struct.SslContextBuilder.html#method.options
+    /// [`SSL_get_options`]: https://www.openssl.org/docs/manmaster/man3/SSL_CTX_set_options.html
+    pub fn options(&self) -> SslOptions {
+        let bits = unsafe { ffi::SSL_get_options(self.as_ptr()) };
+        SslOptions { bits }
+    }
+
+    /// Like [`SslContextBuilder::clear_options`].
+    ///
+    /// This corresponds to [`SSL_clear_options`].
+    ///
+    /// [`SslContextBuilder::clear_options`]: struct.SslContextBuilder.html#method.clear_options
+    /// [`SSL_clear_options`]: https://www.openssl.org/docs/manmaster/man3/SSL_CTX_set_options.html
+    pub fn clear_options(&mut self, option: SslOptions) -> SslOptions {
+        let bits = unsafe { ffi::SSL_clear_options(self.as_ptr(), option.bits()) };
+        SslOptions { bits }
+    }
+
+    /// Like [`SslContextBuilder::set_min_proto_version`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_set_min_proto_version`].
+    ///
+    /// [`SslContextBuilder::set_min_proto_version`]: struct.SslContextBuilder.html#method.set_min_proto_version
+    /// [`SSL_set_min_proto_version`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_set_min_proto_version.html
+    #[cfg(ossl110)]
+    pub fn set_min_proto_version(&mut self, version: Option<SslVersion>) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_set_min_proto_version(
+                self.as_ptr(),
+                version.map_or(0, |v| v.0 as _),
+            )).map(|_| ())
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_max_proto_version`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_set_max_proto_version`].
+    ///
+    /// [`SslContextBuilder::set_max_proto_version`]: struct.SslContextBuilder.html#method.set_max_proto_version
+    /// [`SSL_set_max_proto_version`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_set_min_proto_version.html
+    #[cfg(ossl110)]
+    pub fn set_max_proto_version(&mut self, version: Option<SslVersion>) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_set_max_proto_version(
+                self.as_ptr(),
+                version.map_or(0, |v| v.0 as _),
+            )).map(|_| ())
+        }
+    }
+
+    /// Like [`SslContextBuilder::min_proto_version`].
+    ///
+    /// Requires OpenSSL 1.1.0g or newer.
+    ///
+    /// This corresponds to [`SSL_get_min_proto_version`].
+    ///
+    /// [`SslContextBuilder::min_proto_version`]: struct.SslContextBuilder.html#method.min_proto_version
+    /// [`SSL_get_min_proto_version`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_set_min_proto_version.html
+    #[cfg(ossl110g)]
+    pub fn min_proto_version(&self) -> Option<SslVersion> {
+        unsafe {
+            let r = ffi::SSL_get_min_proto_version(self.as_ptr());
+            if r == 0 {
+                None
+            } else {
+                Some(SslVersion(r))
+            }
+        }
+    }
+
+    /// Like [`SslContextBuilder::max_proto_version`].
+    ///
+    /// Requires OpenSSL 1.1.0g or newer.
+    ///
+    /// This corresponds to [`SSL_get_max_proto_version`].
+    ///
+    /// [`SslContextBuilder::max_proto_version`]: struct.SslContextBuilder.html#method.max_proto_version
+    /// [`SSL_get_max_proto_version`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_set_min_proto_version.html
+    #[cfg(ossl110g)]
+    pub fn max_proto_version(&self) -> Option<SslVersion> {
+        unsafe {
+            let r = ffi::SSL_get_max_proto_version(self.as_ptr());
+            if r == 0 {
+                None
+            } else {
+                Some(SslVersion(r))
+            }
+        }
+    }
+
     /// Like [`SslContextBuilder::set_tmp_dh`].
     ///
     /// This corresponds to [`SSL_set_tmp_dh`].
@@ -2254,78 +3587,247 @@ impl SslRef {
         unsafe { cvt(ffi::SSL_set_tmp_ecdh(self.as_ptr(), key.as_ptr()) as c_int).map(|_| ()) }
     }
 
-    /// Like [`SslContextBuilder::set_tmp_ecdh_callback`].
+    /// Like [`SslContextBuilder::set_tmp_ecdh_callback`].
+    ///
+    /// Requires OpenSSL 1.0.1 or 1.0.2.
+    ///
+    /// This corresponds to `SSL_set_tmp_ecdh_callback`.
+    ///
+    /// [`SslContextBuilder::set_tmp_ecdh_callback`]: struct.SslContextBuilder.html#method.set_tmp_ecdh_callback
+    #[cfg(any(all(ossl101, not(ossl110))))]
+    pub fn set_tmp_ecdh_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, bool, u32) -> Result<EcKey<Params>, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            // this needs to be in an Arc since the callback can register a new callback!
+            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(callback));
+            ffi::SSL_set_tmp_ecdh_callback(self.as_ptr(), raw_tmp_ecdh_ssl::<F>);
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_ecdh_auto`].
+    ///
+    /// Requires OpenSSL 1.0.2.
+    ///
+    /// This corresponds to [`SSL_set_ecdh_auto`].
+    ///
+    /// [`SslContextBuilder::set_tmp_ecdh`]: struct.SslContextBuilder.html#method.set_tmp_ecdh
+    /// [`SSL_set_ecdh_auto`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_set_ecdh_auto.html
+    #[cfg(all(ossl102, not(ossl110)))]
+    pub fn set_ecdh_auto(&mut self, onoff: bool) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_ecdh_auto(self.as_ptr(), onoff as c_int)).map(|_| ()) }
+    }
+
+    /// Like [`SslContextBuilder::set_alpn_protos`].
+    ///
+    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    ///
+    /// This corresponds to [`SSL_set_alpn_protos`].
+    ///
+    /// [`SslContextBuilder::set_alpn_protos`]: struct.SslContextBuilder.html#method.set_alpn_protos
+    /// [`SSL_set_alpn_protos`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_set_alpn_protos.html
+    #[cfg(any(ossl102, libressl261))]
+    pub fn set_alpn_protos(&mut self, protocols: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(protocols.len() <= c_uint::max_value() as usize);
+            let r = ffi::SSL_set_alpn_protos(
+                self.as_ptr(),
+                protocols.as_ptr(),
+                protocols.len() as c_uint,
+            );
+            // fun fact, SSL_set_alpn_protos has a reversed return code D:
+            if r == 0 {
+                Ok(())
+            } else {
+                Err(ErrorStack::get())
+            }
+        }
+    }
+
+    /// Like [`SslContextBuilder::use_psk_identity_hint`].
+    ///
+    /// This corresponds to [`SSL_use_psk_identity_hint`].
+    ///
+    /// [`SslContextBuilder::use_psk_identity_hint`]: struct.SslContextBuilder.html#method.use_psk_identity_hint
+    /// [`SSL_use_psk_identity_hint`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_use_psk_identity_hint.html
+    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
+    pub fn use_psk_identity_hint(&mut self, hint: &str) -> Result<(), ErrorStack> {
+        let hint = CString::new(hint).unwrap();
+        unsafe {
+            cvt(ffi::SSL_use_psk_identity_hint(
+                self.as_ptr(),
+                hint.as_ptr() as *const _,
+            )).map(|_| ())
+        }
+    }
+
+    /// Returns the current cipher if the session is active.
+    ///
+    /// This corresponds to [`SSL_get_current_cipher`].
+    ///
+    /// [`SSL_get_current_cipher`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_get_current_cipher.html
+    pub fn current_cipher(&self) -> Option<&SslCipherRef> {
+        unsafe {
+            let ptr = ffi::SSL_get_current_cipher(self.as_ptr());
+
+            if ptr.is_null() {
+                None
+            } else {
+                Some(SslCipherRef::from_ptr(ptr as *mut _))
+            }
+        }
+    }
+
+    /// Looks up a cipher by its two byte IANA identifier, such as `[0x13, 0x01]` for
+    /// `TLS_AES_128_GCM_SHA256`.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CIPHER_find`].
+    ///
+    /// [`SSL_CIPHER_find`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CIPHER_find.html
+    #[cfg(ossl111)]
+    pub fn find_cipher(&self, id: [u8; 2]) -> Option<&SslCipherRef> {
+        unsafe {
+            let ptr = ffi::SSL_CIPHER_find(self.as_ptr(), id.as_ptr());
+
+            if ptr.is_null() {
+                None
+            } else {
+                Some(SslCipherRef::from_ptr(ptr as *mut _))
+            }
+        }
+    }
+
+    /// Returns the ciphers offered by the client which are supported by this side of the
+    /// connection.
+    ///
+    /// This is only available on the server side once the client's hello message has been
+    /// processed.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`SSL_get_client_ciphers`].
+    ///
+    /// [`SSL_get_client_ciphers`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_get_client_ciphers.html
+    #[cfg(ossl110)]
+    pub fn client_ciphers(&self) -> Option<&StackRef<SslCipher>> {
+        unsafe {
+            let ptr = ffi::SSL_get_client_ciphers(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr(ptr))
+            }
+        }
+    }
+
+    /// Returns the signature algorithms advertised by the peer, in order of preference.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_get_sigalgs`].
+    ///
+    /// [`SSL_get_sigalgs`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_sigalgs.html
+    #[cfg(ossl102)]
+    pub fn peer_signature_algorithms(&self) -> Vec<SslSignatureAlgorithm> {
+        unsafe { signature_algorithms(self.as_ptr(), ffi::SSL_get_sigalgs) }
+    }
+
+    /// Returns the signature algorithms supported by both sides of the connection, in order of
+    /// preference.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_get_shared_sigalgs`].
+    ///
+    /// [`SSL_get_shared_sigalgs`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_shared_sigalgs.html
+    #[cfg(ossl102)]
+    pub fn shared_signature_algorithms(&self) -> Vec<SslSignatureAlgorithm> {
+        unsafe { signature_algorithms(self.as_ptr(), ffi::SSL_get_shared_sigalgs) }
+    }
+
+    /// Returns the digest used by the peer to sign the handshake, if known.
     ///
-    /// Requires OpenSSL 1.0.1 or 1.0.2.
+    /// Requires OpenSSL 1.0.2 or newer.
     ///
-    /// This corresponds to `SSL_set_tmp_ecdh_callback`.
+    /// This corresponds to [`SSL_get_peer_signature_nid`].
     ///
-    /// [`SslContextBuilder::set_tmp_ecdh_callback`]: struct.SslContextBuilder.html#method.set_tmp_ecdh_callback
-    #[cfg(any(all(ossl101, not(ossl110))))]
-    pub fn set_tmp_ecdh_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, bool, u32) -> Result<EcKey<Params>, ErrorStack> + 'static + Sync + Send,
-    {
+    /// [`SSL_get_peer_signature_nid`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_peer_signature_nid.html
+    #[cfg(ossl102)]
+    pub fn peer_signature_digest(&self) -> Option<Nid> {
         unsafe {
-            // this needs to be in an Arc since the callback can register a new callback!
-            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(callback));
-            ffi::SSL_set_tmp_ecdh_callback(self.as_ptr(), raw_tmp_ecdh_ssl::<F>);
+            let mut nid = 0;
+            if ffi::SSL_get_peer_signature_nid(self.as_ptr(), &mut nid) == 1 {
+                Some(Nid::from_raw(nid))
+            } else {
+                None
+            }
         }
     }
 
-    /// Like [`SslContextBuilder::set_ecdh_auto`].
+    /// Returns the public key algorithm used by the peer to sign the handshake, if known.
     ///
-    /// Requires OpenSSL 1.0.2.
+    /// RSA-PSS signatures are reported as `Nid::RSASSAPSS`, even when made with an RSA key.
     ///
-    /// This corresponds to [`SSL_set_ecdh_auto`].
+    /// Requires OpenSSL 1.1.1 or newer.
     ///
-    /// [`SslContextBuilder::set_tmp_ecdh`]: struct.SslContextBuilder.html#method.set_tmp_ecdh
-    /// [`SSL_set_ecdh_auto`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_set_ecdh_auto.html
-    #[cfg(all(ossl102, not(ossl110)))]
-    pub fn set_ecdh_auto(&mut self, onoff: bool) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_set_ecdh_auto(self.as_ptr(), onoff as c_int)).map(|_| ()) }
+    /// This corresponds to [`SSL_get_peer_signature_type_nid`].
+    ///
+    /// [`SSL_get_peer_signature_type_nid`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_peer_signature_type_nid.html
+    #[cfg(ossl111)]
+    pub fn peer_signature_type(&self) -> Option<Nid> {
+        unsafe {
+            let mut nid = 0;
+            if ffi::SSL_get_peer_signature_type_nid(self.as_ptr(), &mut nid) == 1 {
+                Some(Nid::from_raw(nid))
+            } else {
+                None
+            }
+        }
     }
 
-    /// Like [`SslContextBuilder::set_alpn_protos`].
+    /// Returns the ephemeral public key sent by the peer for key exchange, if any.
     ///
-    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    /// Before OpenSSL 1.1.1, this is only available on the client side.
     ///
-    /// This corresponds to [`SSL_set_alpn_protos`].
+    /// Requires OpenSSL 1.0.2 or newer.
     ///
-    /// [`SslContextBuilder::set_alpn_protos`]: struct.SslContextBuilder.html#method.set_alpn_protos
-    /// [`SSL_set_alpn_protos`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_set_alpn_protos.html
-    #[cfg(any(ossl102, libressl261))]
-    pub fn set_alpn_protos(&mut self, protocols: &[u8]) -> Result<(), ErrorStack> {
+    /// This corresponds to [`SSL_get_peer_tmp_key`].
+    ///
+    /// [`SSL_get_peer_tmp_key`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_peer_tmp_key.html
+    #[cfg(ossl102)]
+    pub fn peer_tmp_key(&self) -> Option<PKey<Public>> {
         unsafe {
-            assert!(protocols.len() <= c_uint::max_value() as usize);
-            let r = ffi::SSL_set_alpn_protos(
-                self.as_ptr(),
-                protocols.as_ptr(),
-                protocols.len() as c_uint,
-            );
-            // fun fact, SSL_set_alpn_protos has a reversed return code D:
-            if r == 0 {
-                Ok(())
+            let mut key = ptr::null_mut();
+            if ffi::SSL_get_peer_tmp_key(self.as_ptr(), &mut key) == 1 {
+                Some(PKey::from_ptr(key))
             } else {
-                Err(ErrorStack::get())
+                None
             }
         }
     }
 
-    /// Returns the current cipher if the session is active.
+    /// Returns the elliptic curve or other group negotiated for key exchange, if known.
     ///
-    /// This corresponds to [`SSL_get_current_cipher`].
+    /// The group is determined from the peer's ephemeral key, so it is not available when the key
+    /// exchange did not use a named group, or on the server side of a connection using a protocol
+    /// before TLSv1.3.
     ///
-    /// [`SSL_get_current_cipher`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_get_current_cipher.html
-    pub fn current_cipher(&self) -> Option<&SslCipherRef> {
-        unsafe {
-            let ptr = ffi::SSL_get_current_cipher(self.as_ptr());
-
-            if ptr.is_null() {
-                None
-            } else {
-                Some(SslCipherRef::from_ptr(ptr as *mut _))
-            }
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn negotiated_group(&self) -> Option<Nid> {
+        let key = match self.peer_tmp_key() {
+            Some(key) => key,
+            None => return None,
+        };
+        if key.id() == Id::EC {
+            key.ec_key().ok().and_then(|key| key.group().curve_name())
+        } else if key.id() == Id::DH {
+            None
+        } else {
+            Some(Nid::from_raw(key.id().as_raw()))
         }
     }
 
@@ -2463,6 +3965,41 @@ impl SslRef {
         }
     }
 
+    /// Like [`SslContextBuilder::set_certificate`].
+    ///
+    /// This corresponds to [`SSL_use_certificate`].
+    ///
+    /// [`SslContextBuilder::set_certificate`]: struct.SslContextBuilder.html#method.set_certificate
+    /// [`SSL_use_certificate`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_use_certificate.html
+    pub fn set_certificate(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_use_certificate(self.as_ptr(), cert.as_ptr())).map(|_| ()) }
+    }
+
+    /// Like [`SslContextBuilder::set_private_key`].
+    ///
+    /// This corresponds to [`SSL_use_PrivateKey`].
+    ///
+    /// [`SslContextBuilder::set_private_key`]: struct.SslContextBuilder.html#method.set_private_key
+    /// [`SSL_use_PrivateKey`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_use_certificate.html
+    pub fn set_private_key<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe { cvt(ffi::SSL_use_PrivateKey(self.as_ptr(), key.as_ptr())).map(|_| ()) }
+    }
+
+    /// Appends a certificate to the chain of the connection's current certificate.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// This corresponds to [`SSL_add1_chain_cert`].
+    ///
+    /// [`SSL_add1_chain_cert`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_add1_chain_cert.html
+    #[cfg(ossl102)]
+    pub fn add_chain_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_add1_chain_cert(self.as_ptr(), cert.as_ptr()) as c_int).map(|_| ()) }
+    }
+
     #[deprecated(since = "0.10.5", note = "renamed to `version_str`")]
     pub fn version(&self) -> &str {
         self.version_str()
@@ -2582,6 +4119,33 @@ impl SslRef {
         }
     }
 
+    /// Derives the SRTP master keys and salts for the negotiated protection profile.
+    ///
+    /// Returns `Ok(None)` if no profile was negotiated, or if the key and salt lengths of the
+    /// negotiated profile are not known.
+    ///
+    /// DTLS extension "use_srtp" as defined in RFC5764 has to be enabled.
+    pub fn srtp_keying_material(&self) -> Result<Option<SrtpKeyingMaterial>, ErrorStack> {
+        let profile = match self.selected_srtp_profile() {
+            Some(profile) => profile.id(),
+            None => return Ok(None),
+        };
+        let (key_len, salt_len) = match (profile.master_key_len(), profile.master_salt_len()) {
+            (Some(key_len), Some(salt_len)) => (key_len, salt_len),
+            _ => return Ok(None),
+        };
+
+        let mut material = vec![0; 2 * (key_len + salt_len)];
+        self.export_keying_material(&mut material, "EXTRACTOR-dtls_srtp", None)?;
+        Ok(Some(SrtpKeyingMaterial::new(
+            profile,
+            material,
+            key_len,
+            salt_len,
+            self.is_server(),
+        )))
+    }
+
     /// Returns the number of bytes remaining in the currently processed TLS record.
     ///
     /// If this is greater than 0, the next call to `read` will not call down to the underlying
@@ -2925,6 +4489,50 @@ impl SslRef {
         }
     }
 
+    /// Sets the maximum size of DTLS datagrams, excluding IP and UDP headers.
+    ///
+    /// Values below the minimum supported by the DTLS implementation are rejected.
+    ///
+    /// This corresponds to [`SSL_set_mtu`].
+    ///
+    /// [`SSL_set_mtu`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_set_mtu.html
+    pub fn set_mtu(&mut self, mtu: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_mtu(self.as_ptr(), mtu as c_long) as c_int).map(|_| ()) }
+    }
+
+    /// Returns the time remaining before the DTLS retransmission timer expires.
+    ///
+    /// Returns `None` if the timer is not running. If the returned duration is zero, the timer
+    /// has expired and `handle_dtls_timeout` should be called.
+    ///
+    /// This corresponds to [`DTLSv1_get_timeout`].
+    ///
+    /// [`DTLSv1_get_timeout`]: https://www.openssl.org/docs/man1.1.1/man3/DTLSv1_get_timeout.html
+    pub fn dtls_timeout(&self) -> Option<Duration> {
+        unsafe {
+            let mut tv = mem::zeroed::<timeval>();
+            if ffi::DTLSv1_get_timeout(self.as_ptr(), &mut tv) > 0 {
+                Some(Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Retransmits the last DTLS handshake flight if the retransmission timer has expired.
+    ///
+    /// Returns `true` if a retransmission was performed, and `false` if the timer had not yet
+    /// expired. An error is returned if too many retransmissions have failed.
+    ///
+    /// This corresponds to [`DTLSv1_handle_timeout`].
+    ///
+    /// [`DTLSv1_handle_timeout`]: https://www.openssl.org/docs/man1.1.1/man3/DTLSv1_handle_timeout.html
+    pub fn handle_dtls_timeout(&mut self) -> Result<bool, ErrorStack> {
+        unsafe {
+            cvt_n(ffi::DTLSv1_handle_timeout(self.as_ptr()) as c_int).map(|r| r > 0)
+        }
+    }
+
     /// Sets the maximum amount of early data that will be accepted on this connection.
     ///
     /// Requires OpenSSL 1.1.1 or newer.
@@ -2953,6 +4561,76 @@ impl SslRef {
         unsafe { ffi::SSL_get_max_early_data(self.as_ptr()) }
     }
 
+    /// Returns whether early data was sent and accepted.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_get_early_data_status`].
+    ///
+    /// [`SSL_get_early_data_status`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_early_data_status.html
+    #[cfg(ossl111)]
+    pub fn early_data_status(&self) -> SslEarlyDataStatus {
+        unsafe { SslEarlyDataStatus(ffi::SSL_get_early_data_status(self.as_ptr())) }
+    }
+
+    /// Sets whether a client will send a certificate if the server requests one after the
+    /// handshake.
+    ///
+    /// This must be called before the handshake. It is disabled by default.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_set_post_handshake_auth`].
+    ///
+    /// [`SSL_set_post_handshake_auth`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_set_post_handshake_auth.html
+    #[cfg(ossl111)]
+    pub fn set_post_handshake_auth(&mut self, enabled: bool) {
+        unsafe { ffi::SSL_set_post_handshake_auth(self.as_ptr(), enabled as c_int) }
+    }
+
+    /// Requests a certificate from the client once the TLSv1.3 handshake has completed.
+    ///
+    /// The request is only queued; it is sent by the next write or call to
+    /// `SslStream::do_handshake`, and the client's certificate is processed by a later read.
+    /// The client must have enabled post-handshake authentication.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_verify_client_post_handshake`].
+    ///
+    /// [`SSL_verify_client_post_handshake`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_verify_client_post_handshake.html
+    #[cfg(ossl111)]
+    pub fn verify_client_post_handshake(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_verify_client_post_handshake(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Schedules an update of the TLSv1.3 traffic keys.
+    ///
+    /// The update is only queued; it is sent by the next write or call to
+    /// `SslStream::do_handshake`.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_key_update`].
+    ///
+    /// [`SSL_key_update`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_key_update.html
+    #[cfg(ossl111)]
+    pub fn key_update(&mut self, update_type: SslKeyUpdateType) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_key_update(self.as_ptr(), update_type.0)).map(|_| ()) }
+    }
+
+    /// Returns the type of the key update waiting to be sent, or `SslKeyUpdateType::NONE`.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_get_key_update_type`].
+    ///
+    /// [`SSL_get_key_update_type`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_key_update_type.html
+    #[cfg(ossl111)]
+    pub fn key_update_type(&self) -> SslKeyUpdateType {
+        unsafe { SslKeyUpdateType(ffi::SSL_get_key_update_type(self.as_ptr())) }
+    }
+
     /// Copies the contents of the last Finished message sent to the peer into the provided buffer.
     ///
     /// The total size of the message is returned, so this can be used to determine the size of the
@@ -3100,6 +4778,159 @@ impl SslRef {
             }
         }
     }
+
+    /// Returns the types of the extensions in the client's hello message, in the order they were
+    /// sent.
+    ///
+    /// Only extensions recognized by OpenSSL, or registered with
+    /// `SslContextBuilder::add_custom_ext`, are reported.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_client_hello_get1_extensions_present`].
+    ///
+    /// [`SSL_client_hello_get1_extensions_present`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_client_hello_cb.html
+    #[cfg(ossl111)]
+    pub fn client_hello_extensions_present(&self) -> Option<Vec<u16>> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let mut len = 0;
+            if ffi::SSL_client_hello_get1_extensions_present(self.as_ptr(), &mut ptr, &mut len)
+                != 1
+            {
+                return None;
+            }
+            let extensions = if len == 0 {
+                vec![]
+            } else {
+                slice::from_raw_parts(ptr, len)
+                    .iter()
+                    .map(|&t| t as u16)
+                    .collect()
+            };
+            ffi::CRYPTO_free(
+                ptr as *mut c_void,
+                concat!(file!(), "\0").as_ptr() as *const c_char,
+                line!() as c_int,
+            );
+            Some(extensions)
+        }
+    }
+
+    /// Returns the body of an extension in the client's hello message.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    /// `None` is also returned if the client did not send the extension.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_client_hello_get0_ext`].
+    ///
+    /// [`SSL_client_hello_get0_ext`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_client_hello_cb.html
+    #[cfg(ossl111)]
+    pub fn client_hello_ext(&self, ext_type: u16) -> Option<&[u8]> {
+        unsafe {
+            let mut ptr = ptr::null();
+            let mut len = 0;
+            if ffi::SSL_client_hello_get0_ext(self.as_ptr(), ext_type as c_uint, &mut ptr, &mut len)
+                != 1
+            {
+                None
+            } else if len == 0 {
+                Some(&[])
+            } else {
+                Some(slice::from_raw_parts(ptr, len))
+            }
+        }
+    }
+
+    /// Returns the groups listed in the `supported_groups` extension of the client's hello
+    /// message, as their IANA code points.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    /// `None` is also returned if the extension is missing or malformed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_supported_groups(&self) -> Option<Vec<u16>> {
+        self.client_hello_ext(client_hello::EXT_SUPPORTED_GROUPS)
+            .and_then(client_hello::parse_supported_groups)
+    }
+
+    /// Returns the point formats listed in the `ec_point_formats` extension of the client's hello
+    /// message.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    /// `None` is also returned if the extension is missing or malformed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_ec_point_formats(&self) -> Option<Vec<u8>> {
+        self.client_hello_ext(client_hello::EXT_EC_POINT_FORMATS)
+            .and_then(client_hello::parse_ec_point_formats)
+    }
+
+    /// Returns the protocol versions listed in the `supported_versions` extension of the client's
+    /// hello message, such as `0x0304` for TLSv1.3.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    /// `None` is also returned if the extension is missing or malformed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_supported_versions(&self) -> Option<Vec<u16>> {
+        self.client_hello_ext(client_hello::EXT_SUPPORTED_VERSIONS)
+            .and_then(client_hello::parse_supported_versions)
+    }
+
+    /// Returns the signature schemes listed in the `signature_algorithms` extension of the
+    /// client's hello message, as their IANA code points.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    /// `None` is also returned if the extension is missing or malformed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_signature_algorithms(&self) -> Option<Vec<u16>> {
+        self.client_hello_ext(client_hello::EXT_SIGNATURE_ALGORITHMS)
+            .and_then(client_hello::parse_signature_algorithms)
+    }
+
+    /// Returns the JA3 fingerprint of the client's hello message.
+    ///
+    /// The fingerprint is built from the extensions reported by `client_hello_extensions_present`,
+    /// so extensions unknown to OpenSSL are omitted.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_ja3(&self) -> Option<Ja3Fingerprint> {
+        let version = match self.client_hello_legacy_version() {
+            Some(version) => version.0 as u16,
+            None => return None,
+        };
+        let ciphers = match self.client_hello_ciphers().and_then(client_hello::u16_list) {
+            Some(ciphers) => ciphers,
+            None => return None,
+        };
+        let extensions = match self.client_hello_extensions_present() {
+            Some(extensions) => extensions,
+            None => return None,
+        };
+        let groups = self.client_hello_supported_groups().unwrap_or_default();
+        let point_formats = self.client_hello_ec_point_formats().unwrap_or_default();
+
+        Some(Ja3Fingerprint::client(
+            version,
+            &ciphers,
+            &extensions,
+            &groups,
+            &point_formats,
+        ))
+    }
 }
 
 /// An SSL stream midway through the handshake process.
@@ -3141,7 +4972,7 @@ impl<S> MidHandshakeSslStream<S> {
     ///
     /// [`SSL_do_handshake`]: https://www.openssl.org/docs/manmaster/man3/SSL_do_handshake.html
     pub fn handshake(mut self) -> Result<SslStream<S>, HandshakeError<S>> {
-        let ret = unsafe { ffi::SSL_do_handshake(self.stream.ssl.as_ptr()) };
+        let ret = unsafe { timed_handshake(self.stream.ssl.as_ptr(), ffi::SSL_do_handshake) };
         if ret > 0 {
             Ok(self.stream)
         } else {
@@ -3291,6 +5122,43 @@ impl<S: Read + Write> SslStream<S> {
     pub fn set_shutdown(&mut self, state: ShutdownState) {
         unsafe { ffi::SSL_set_shutdown(self.ssl.as_ptr(), state.bits()) }
     }
+
+    /// Performs any pending handshake work, such as sending a queued post-handshake message.
+    ///
+    /// This corresponds to [`SSL_do_handshake`].
+    ///
+    /// [`SSL_do_handshake`]: https://www.openssl.org/docs/manmaster/man3/SSL_do_handshake.html
+    pub fn do_handshake(&mut self) -> Result<(), Error> {
+        let ret = unsafe { timed_handshake(self.ssl.as_ptr(), ffi::SSL_do_handshake) };
+        if ret > 0 {
+            Ok(())
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Requests a certificate from the client, sending the request immediately.
+    ///
+    /// The client's certificate is processed by a later read, after which it is available from
+    /// `SslRef::peer_certificate`. See `SslRef::verify_client_post_handshake` for details.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn verify_client_post_handshake(&mut self) -> Result<(), Error> {
+        self.ssl.verify_client_post_handshake()?;
+        self.do_handshake()
+    }
+
+    /// Updates the TLSv1.3 traffic keys, sending the update immediately.
+    ///
+    /// See `SslRef::key_update` for details.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn key_update(&mut self, update_type: SslKeyUpdateType) -> Result<(), Error> {
+        self.ssl.key_update(update_type)?;
+        self.do_handshake()
+    }
 }
 
 impl<S> SslStream<S> {
@@ -3457,7 +5325,7 @@ where
     /// See `Ssl::connect`
     pub fn connect(self) -> Result<SslStream<S>, HandshakeError<S>> {
         let mut stream = self.inner;
-        let ret = unsafe { ffi::SSL_connect(stream.ssl.as_ptr()) };
+        let ret = unsafe { timed_handshake(stream.ssl.as_ptr(), ffi::SSL_connect) };
         if ret > 0 {
             Ok(stream)
         } else {
@@ -3480,7 +5348,7 @@ where
     /// See `Ssl::accept`
     pub fn accept(self) -> Result<SslStream<S>, HandshakeError<S>> {
         let mut stream = self.inner;
-        let ret = unsafe { ffi::SSL_accept(stream.ssl.as_ptr()) };
+        let ret = unsafe { timed_handshake(stream.ssl.as_ptr(), ffi::SSL_accept) };
         if ret > 0 {
             Ok(stream)
         } else {
@@ -3509,7 +5377,7 @@ where
     /// [`SSL_do_handshake`]: https://www.openssl.org/docs/manmaster/man3/SSL_do_handshake.html
     pub fn handshake(self) -> Result<SslStream<S>, HandshakeError<S>> {
         let mut stream = self.inner;
-        let ret = unsafe { ffi::SSL_do_handshake(stream.ssl.as_ptr()) };
+        let ret = unsafe { timed_handshake(stream.ssl.as_ptr(), ffi::SSL_do_handshake) };
         if ret > 0 {
             Ok(stream)
         } else {
@@ -0,0 +1,172 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ssl::SslSessionRef;
+
+/// An external store for server-side sessions.
+///
+/// Sessions are stored in their DER encoding, keyed by session ID, so that the store can be
+/// backed by a service shared between servers. The cache is installed with
+/// `SslContextBuilder::set_session_cache`.
+///
+/// Sessions are stored when a full handshake completes, looked up when a client proposes to
+/// resume a session, and removed when OpenSSL determines that they have expired or can no longer
+/// be used. Expired sessions returned by `get` are rejected, so implementations only need to
+/// honor the timeout passed to `put` as a hint for eviction.
+pub trait SessionCache: 'static + Sync + Send {
+    /// Stores a DER-encoded session, which remains valid for `timeout`.
+    fn put(&self, id: &[u8], session: Vec<u8>, timeout: Duration);
+
+    /// Returns the DER-encoded session with the specified ID, if present.
+    fn get(&self, id: &[u8]) -> Option<Vec<u8>>;
+
+    /// Removes the session with the specified ID.
+    fn remove(&self, id: &[u8]);
+}
+
+impl<T: SessionCache> SessionCache for Arc<T> {
+    fn put(&self, id: &[u8], session: Vec<u8>, timeout: Duration) {
+        (**self).put(id, session, timeout)
+    }
+
+    fn get(&self, id: &[u8]) -> Option<Vec<u8>> {
+        (**self).get(id)
+    }
+
+    fn remove(&self, id: &[u8]) {
+        (**self).remove(id)
+    }
+}
+
+pub(crate) fn session_timeout(session: &SslSessionRef) -> Duration {
+    Duration::from_secs(u64::from(session.timeout()))
+}
+
+struct Entry {
+    session: Vec<u8>,
+    expires: Instant,
+    last_used: u64,
+}
+
+struct Lru {
+    entries: HashMap<Vec<u8>, Entry>,
+    order: BTreeMap<u64, Vec<u8>>,
+    tick: u64,
+}
+
+impl Lru {
+    fn next_tick(&mut self) -> u64 {
+        let tick = self.tick;
+        self.tick += 1;
+        tick
+    }
+
+    fn touch(&mut self, id: &[u8]) {
+        let tick = self.next_tick();
+        if let Some(entry) = self.entries.get_mut(id) {
+            self.order.remove(&entry.last_used);
+            entry.last_used = tick;
+            self.order.insert(tick, id.to_vec());
+        }
+    }
+
+    fn remove(&mut self, id: &[u8]) -> Option<Entry> {
+        let entry = self.entries.remove(id);
+        if let Some(ref entry) = entry {
+            self.order.remove(&entry.last_used);
+        }
+        entry
+    }
+
+    fn pop_oldest(&mut self) {
+        let oldest = self.order.keys().next().cloned();
+        if let Some(tick) = oldest {
+            let id = self.order.remove(&tick).unwrap();
+            self.entries.remove(&id);
+        }
+    }
+}
+
+/// A `SessionCache` which holds sessions in memory.
+///
+/// Once the cache is full, the least recently used session is evicted. Expired sessions are
+/// dropped when they are looked up.
+///
+/// Wrapping the cache in an `Arc` allows it to be shared between contexts.
+pub struct MemorySessionCache {
+    lru: Mutex<Lru>,
+    capacity: usize,
+}
+
+impl MemorySessionCache {
+    /// Creates a cache which holds up to `capacity` sessions.
+    pub fn new(capacity: usize) -> MemorySessionCache {
+        MemorySessionCache {
+            lru: Mutex::new(Lru {
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                tick: 0,
+            }),
+            capacity,
+        }
+    }
+
+    /// Returns the number of sessions in the cache, including expired ones not yet dropped.
+    pub fn len(&self) -> usize {
+        self.lru.lock().unwrap().entries.len()
+    }
+
+    /// Returns `true` if the cache holds no sessions.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all sessions from the cache.
+    pub fn clear(&self) {
+        let mut lru = self.lru.lock().unwrap();
+        lru.entries.clear();
+        lru.order.clear();
+    }
+}
+
+impl SessionCache for MemorySessionCache {
+    fn put(&self, id: &[u8], session: Vec<u8>, timeout: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut lru = self.lru.lock().unwrap();
+        lru.remove(id);
+        while lru.entries.len() >= self.capacity {
+            lru.pop_oldest();
+        }
+        let tick = lru.next_tick();
+        let entry = Entry {
+            session,
+            expires: Instant::now() + timeout,
+            last_used: tick,
+        };
+        lru.entries.insert(id.to_vec(), entry);
+        lru.order.insert(tick, id.to_vec());
+    }
+
+    fn get(&self, id: &[u8]) -> Option<Vec<u8>> {
+        let mut lru = self.lru.lock().unwrap();
+        let expired = match lru.entries.get(id) {
+            Some(entry) => entry.expires <= Instant::now(),
+            None => return None,
+        };
+        if expired {
+            lru.remove(id);
+            return None;
+        }
+
+        lru.touch(id);
+        lru.entries.get(id).map(|e| e.session.clone())
+    }
+
+    fn remove(&self, id: &[u8]) {
+        self.lru.lock().unwrap().remove(id);
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use error::ErrorStack;
+use ffi;
+use foreign_types::ForeignTypeRef;
+use ssl::{Ssl, SslRef};
+use stack::{Stack, StackRef};
+use x509::store::X509Store;
+use x509::{X509Ref, X509StoreContext, X509StoreContextRef, X509VerifyResult, X509};
+
+const MAX_ID_LEN: usize = 2048;
+
+/// A SPIFFE ID of the form `spiffe://trust-domain/path`.
+///
+/// See the [SPIFFE ID specification] for details.
+///
+/// [SPIFFE ID specification]: https://github.com/spiffe/spiffe/blob/master/standards/SPIFFE-ID.md
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpiffeId {
+    id: String,
+    path: usize,
+}
+
+impl SpiffeId {
+    /// Parses a SPIFFE ID from a URI.
+    ///
+    /// Returns `None` if the URI is not a valid SPIFFE ID. The scheme and trust domain must be
+    /// lowercase, and query strings, fragments, ports and user info are not permitted.
+    pub fn from_uri(uri: &str) -> Option<SpiffeId> {
+        const SCHEME: &'static str = "spiffe://";
+
+        if uri.len() > MAX_ID_LEN || !uri.starts_with(SCHEME) {
+            return None;
+        }
+
+        let rest = &uri[SCHEME.len()..];
+        let path = rest.find('/').unwrap_or(rest.len());
+        let (trust_domain, path_str) = rest.split_at(path);
+
+        if !is_valid_trust_domain(trust_domain) {
+            return None;
+        }
+
+        if !path_str.is_empty() && !path_str[1..].split('/').all(is_valid_path_segment) {
+            return None;
+        }
+
+        Some(SpiffeId {
+            id: uri.to_string(),
+            path: SCHEME.len() + path,
+        })
+    }
+
+    /// Returns the trust domain of the ID, e.g. `example.org`.
+    pub fn trust_domain(&self) -> &str {
+        &self.id["spiffe://".len()..self.path]
+    }
+
+    /// Returns the path of the ID, e.g. `/service`.
+    ///
+    /// The path is empty for IDs which identify the trust domain itself.
+    pub fn path(&self) -> &str {
+        &self.id[self.path..]
+    }
+
+    /// Returns the ID as a URI.
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+}
+
+impl fmt::Display for SpiffeId {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(&self.id)
+    }
+}
+
+fn is_valid_trust_domain(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| is_lower_alnum(b) || b == b'.' || b == b'-' || b == b'_')
+}
+
+fn is_valid_path_segment(s: &str) -> bool {
+    s != "."
+        && s != ".."
+        && !s.is_empty()
+        && s.bytes().all(|b| {
+            is_lower_alnum(b) || (b >= b'A' && b <= b'Z') || b == b'.' || b == b'-' || b == b'_'
+        })
+}
+
+fn is_lower_alnum(b: u8) -> bool {
+    (b >= b'a' && b <= b'z') || (b >= b'0' && b <= b'9')
+}
+
+/// Returns the SPIFFE ID of a certificate.
+///
+/// An X.509 SVID must contain exactly one URI subject alternative name, which must be a valid
+/// SPIFFE ID.
+fn certificate_id(cert: &X509Ref) -> Option<SpiffeId> {
+    let names = match cert.subject_alt_names() {
+        Some(names) => names,
+        None => return None,
+    };
+    let mut uris = names.iter().filter_map(|n| n.uri());
+    match (uris.next(), uris.next()) {
+        (Some(uri), None) => SpiffeId::from_uri(uri),
+        _ => None,
+    }
+}
+
+/// The outcome of SPIFFE verification of a connection's peer, stored in the `Ssl`'s extra data.
+struct SpiffeState(Result<SpiffeId, X509VerifyResult>);
+
+/// A policy used to authenticate peers by their SPIFFE ID.
+///
+/// The peer's leaf certificate must contain exactly one URI subject alternative name holding a
+/// valid SPIFFE ID. If any IDs, trust domains or a predicate have been configured, the ID must
+/// match at least one of them. If none have been configured, any valid SPIFFE ID is accepted.
+///
+/// If trust bundles have been added, the peer's certificate chain is verified against the bundle
+/// of the trust domain of its ID rather than the context's certificate store, and peers from
+/// trust domains without a bundle are rejected.
+///
+/// The verifier is installed with `SslConnectorBuilder::set_spiffe_verifier` or
+/// `SslAcceptorBuilder::set_spiffe_verifier`, and the authenticated ID can be retrieved with
+/// `SslRef::spiffe_id` after the handshake.
+pub struct SpiffeVerifier {
+    ids: HashSet<SpiffeId>,
+    trust_domains: HashSet<String>,
+    predicate: Option<Box<Fn(&SpiffeId) -> bool + 'static + Sync + Send>>,
+    bundles: HashMap<String, X509Store>,
+}
+
+impl SpiffeVerifier {
+    /// Creates a new verifier which accepts any SPIFFE ID.
+    ///
+    /// An empty allow-list does not reject anything: until `allow_id`, `allow_trust_domain` or
+    /// `set_predicate` is called, every peer with a valid SPIFFE ID and a trusted certificate chain
+    /// is accepted.
+    pub fn new() -> SpiffeVerifier {
+        SpiffeVerifier {
+            ids: HashSet::new(),
+            trust_domains: HashSet::new(),
+            predicate: None,
+            bundles: HashMap::new(),
+        }
+    }
+
+    /// Accepts peers presenting the specified ID.
+    pub fn allow_id(&mut self, id: SpiffeId) {
+        self.ids.insert(id);
+    }
+
+    /// Accepts peers presenting any ID in the specified trust domain.
+    pub fn allow_trust_domain(&mut self, trust_domain: &str) {
+        self.trust_domains.insert(trust_domain.to_string());
+    }
+
+    /// Accepts peers presenting IDs for which the predicate returns `true`.
+    ///
+    /// This replaces any previously configured predicate.
+    pub fn set_predicate<F>(&mut self, predicate: F)
+    where
+        F: Fn(&SpiffeId) -> bool + 'static + Sync + Send,
+    {
+        self.predicate = Some(Box::new(predicate));
+    }
+
+    /// Sets the trust bundle used to verify peers from the specified trust domain.
+    pub fn add_bundle(&mut self, trust_domain: &str, bundle: X509Store) {
+        self.bundles.insert(trust_domain.to_string(), bundle);
+    }
+
+    fn allows(&self, id: &SpiffeId) -> bool {
+        if self.ids.is_empty() && self.trust_domains.is_empty() && self.predicate.is_none() {
+            return true;
+        }
+
+        self.ids.contains(id)
+            || self.trust_domains.contains(id.trust_domain())
+            || self.predicate.as_ref().map_or(false, |p| p(id))
+    }
+
+    fn verify_bundle(&self, id: &SpiffeId, x509_ctx: &X509StoreContextRef) -> X509VerifyResult {
+        let bundle = match self.bundles.get(id.trust_domain()) {
+            Some(bundle) => bundle,
+            None => return X509VerifyResult::APPLICATION_VERIFICATION,
+        };
+        let leaf = match x509_ctx.current_cert() {
+            Some(leaf) => leaf,
+            None => return X509VerifyResult::APPLICATION_VERIFICATION,
+        };
+
+        let r = (|| -> Result<X509VerifyResult, ErrorStack> {
+            let mut chain: Stack<X509> = Stack::new()?;
+            if let Some(certs) = untrusted(x509_ctx) {
+                for cert in certs {
+                    chain.push(cert.to_owned())?;
+                }
+            }
+            let mut ctx = X509StoreContext::new()?;
+            ctx.init(bundle, leaf, &chain, |c| {
+                c.verify_cert()?;
+                Ok(c.error())
+            })
+        })();
+
+        r.unwrap_or(X509VerifyResult::APPLICATION_VERIFICATION)
+    }
+
+    fn check(&self, x509_ctx: &X509StoreContextRef) -> Result<SpiffeId, X509VerifyResult> {
+        let id = match x509_ctx.current_cert().and_then(certificate_id) {
+            Some(id) => id,
+            None => return Err(X509VerifyResult::APPLICATION_VERIFICATION),
+        };
+
+        if !self.allows(&id) {
+            return Err(X509VerifyResult::APPLICATION_VERIFICATION);
+        }
+
+        if !self.bundles.is_empty() {
+            let result = self.verify_bundle(&id, x509_ctx);
+            if result != X509VerifyResult::OK {
+                return Err(result);
+            }
+        }
+
+        Ok(id)
+    }
+
+    pub(crate) fn verify(&self, preverify_ok: bool, x509_ctx: &mut X509StoreContextRef) -> bool {
+        // with trust bundles, the chain is verified separately against the peer's trust domain, so
+        // only failures to build a trusted chain from the context's store can be overridden
+        let bundles = !self.bundles.is_empty();
+        if !preverify_ok && (!bundles || !is_chain_error(x509_ctx.error())) {
+            return false;
+        }
+        if x509_ctx.error_depth() != 0 {
+            return true;
+        }
+
+        let ssl = match X509StoreContext::ssl_idx()
+            .ok()
+            .and_then(|idx| x509_ctx.ex_data(idx))
+        {
+            Some(ssl) => ssl.as_ptr(),
+            None => return false,
+        };
+        let ssl = unsafe { SslRef::from_ptr_mut(ssl) };
+        let idx = Ssl::cached_ex_index::<SpiffeState>();
+
+        // the leaf may be reported more than once, so only check it the first time
+        let result = match ssl.ex_data(idx) {
+            Some(state) => state.0.clone(),
+            None => {
+                let result = self.check(x509_ctx);
+                ssl.set_ex_data(idx, SpiffeState(result.clone()));
+                result
+            }
+        };
+
+        match result {
+            Ok(_) => {
+                if bundles && is_chain_error(x509_ctx.error()) {
+                    x509_ctx.set_error(X509VerifyResult::OK);
+                }
+                true
+            }
+            Err(e) => {
+                x509_ctx.set_error(e);
+                false
+            }
+        }
+    }
+}
+
+/// Returns the certificates sent by the peer.
+fn untrusted(x509_ctx: &X509StoreContextRef) -> Option<&StackRef<X509>> {
+    unsafe {
+        let chain = X509_STORE_CTX_get0_untrusted(x509_ctx.as_ptr());
+        if chain.is_null() {
+            None
+        } else {
+            Some(StackRef::from_ptr(chain))
+        }
+    }
+}
+
+/// Determines if an error is a failure to build a trusted chain, which the bundle check replaces.
+fn is_chain_error(error: X509VerifyResult) -> bool {
+    match error.as_raw() {
+        ffi::X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT
+        | ffi::X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT_LOCALLY
+        | ffi::X509_V_ERR_UNABLE_TO_VERIFY_LEAF_SIGNATURE
+        | ffi::X509_V_ERR_DEPTH_ZERO_SELF_SIGNED_CERT
+        | ffi::X509_V_ERR_SELF_SIGNED_CERT_IN_CHAIN
+        | ffi::X509_V_ERR_CERT_UNTRUSTED => true,
+        _ => false,
+    }
+}
+
+cfg_if! {
+    if #[cfg(ossl110)] {
+        use ffi::X509_STORE_CTX_get0_untrusted;
+    } else {
+        // the untrusted certificates are not accessible before 1.1.0, but the chain built from them
+        // is
+        use ffi::X509_STORE_CTX_get_chain as X509_STORE_CTX_get0_untrusted;
+    }
+}
+
+impl SslRef {
+    /// Returns the SPIFFE ID of the peer, if it was authenticated by a `SpiffeVerifier`.
+    pub fn spiffe_id(&self) -> Option<&SpiffeId> {
+        self.ex_data(Ssl::cached_ex_index::<SpiffeState>())
+            .and_then(|state| state.0.as_ref().ok())
+    }
+}
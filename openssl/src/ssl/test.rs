@@ -10,6 +10,7 @@ use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::path::Path;
 use std::process::{Child, ChildStdin, Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering, ATOMIC_BOOL_INIT};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 use tempdir::TempDir;
@@ -23,12 +24,17 @@ use ssl;
 #[cfg(any(ossl110, ossl111, libressl261))]
 use ssl::SslVersion;
 use ssl::{
-    Error, HandshakeError, MidHandshakeSslStream, ShutdownResult, ShutdownState, Ssl, SslAcceptor,
-    SslConnector, SslContext, SslFiletype, SslMethod, SslSessionCacheMode, SslStream,
-    SslVerifyMode, StatusType,
+    DtlsStream, Error, ErrorCode, HandshakeError, MemorySessionCache, MidHandshakeSslStream,
+    SessionCache, SessionTicketKey, SessionTicketKeys, ShutdownResult, ShutdownState, SpiffeId,
+    SpiffeVerifier, Ssl, SslAcceptor, SslConnector, SslContext, SslEngine, SslFiletype, SslMethod,
+    SslOptions, SslRef, SslSession, SslSessionBuilder, SslSessionCacheMode, SslSessionRef,
+    SslStream, SslVerifyMode, StatusType, TicketKeyRequest, TicketKeyResponse, VirtualHosts,
 };
+#[cfg(ossl110)]
+use ssl::DtlsListener;
 #[cfg(any(ossl102, ossl110))]
 use x509::verify::X509CheckFlags;
+use x509::store::X509StoreBuilder;
 use x509::{X509, X509Name, X509StoreContext, X509VerifyResult};
 
 use std::net::UdpSocket;
@@ -36,6 +42,7 @@ use std::net::UdpSocket;
 static ROOT_CERT: &'static [u8] = include_bytes!("../../test/root-ca.pem");
 static CERT: &'static [u8] = include_bytes!("../../test/cert.pem");
 static KEY: &'static [u8] = include_bytes!("../../test/key.pem");
+static SPIFFE_CERT: &'static [u8] = include_bytes!("../../test/spiffe.pem");
 
 fn next_addr() -> SocketAddr {
     use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
@@ -670,6 +677,57 @@ fn test_connect_with_srtp_ssl() {
     assert_eq!(buf[..], buf2[..]);
 }
 
+#[test]
+fn srtp_keying_material() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::dtls()).unwrap();
+        ctx.set_tlsext_use_srtp("SRTP_AEAD_AES_128_GCM:SRTP_AES128_CM_SHA1_80")
+            .unwrap();
+        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        let ssl = Ssl::new(&ctx.build()).unwrap();
+        let mut stream = ssl.accept(stream).unwrap();
+
+        let material = stream.ssl().srtp_keying_material().unwrap().unwrap();
+        stream.write_all(&[0]).unwrap();
+        material
+    });
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut ctx = SslContext::builder(SslMethod::dtls()).unwrap();
+    ctx.set_tlsext_use_srtp("SRTP_AEAD_AES_128_GCM").unwrap();
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+    let mut stream = ssl.connect(stream).unwrap();
+
+    let client = stream.ssl().srtp_keying_material().unwrap().unwrap();
+    let mut buf = [0; 56];
+    stream
+        .ssl()
+        .export_keying_material(&mut buf, "EXTRACTOR-dtls_srtp", None)
+        .unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+
+    let server = guard.join().unwrap();
+
+    assert_eq!(client.profile(), SrtpProfileId::SRTP_AEAD_AES_128_GCM);
+    assert!(!client.is_server());
+    assert!(server.is_server());
+    assert_eq!(client.client_master_key(), &buf[..16]);
+    assert_eq!(client.server_master_key(), &buf[16..32]);
+    assert_eq!(client.client_master_salt(), &buf[32..44]);
+    assert_eq!(client.server_master_salt(), &buf[44..]);
+    assert_eq!(client.local_master_key(), server.remote_master_key());
+    assert_eq!(client.local_master_salt(), server.remote_master_salt());
+    assert_eq!(client.remote_master_key(), server.local_master_key());
+    assert_eq!(client.remote_master_salt(), server.local_master_salt());
+}
+
 /// Tests that when the `SslStream` is created as a server stream, the protocols
 /// are correctly advertised to the client.
 #[test]
@@ -1108,6 +1166,97 @@ fn connector_client_server_mozilla_modern() {
     t.join().unwrap();
 }
 
+fn vhost_context(cert: &str, key: &str) -> SslContext {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(&Path::new(cert), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(&Path::new(key), SslFiletype::PEM)
+        .unwrap();
+    ctx.build()
+}
+
+fn vhost_handshake(acceptor: &SslAcceptor, server_name: &str) -> Option<(Option<String>, Vec<u8>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let acceptor = acceptor.clone();
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut stream = match acceptor.accept(stream) {
+            Ok(stream) => stream,
+            Err(_) => return None,
+        };
+        stream.write_all(&[0]).unwrap();
+        Some(stream.ssl().virtual_host().map(str::to_string))
+    });
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    ssl.set_hostname(server_name).unwrap();
+    let stream = TcpStream::connect(addr).unwrap();
+    let peer = ssl.connect(stream).ok().map(|mut stream| {
+        stream.read_exact(&mut [0]).unwrap();
+        stream.ssl().peer_certificate().unwrap().to_der().unwrap()
+    });
+
+    let host = guard.join().unwrap();
+    match (host, peer) {
+        (Some(host), Some(peer)) => Some((host, peer)),
+        _ => None,
+    }
+}
+
+#[test]
+fn virtual_hosts() {
+    let foobar = vhost_context("test/cert.pem", "test/key.pem");
+    let ca = vhost_context("test/root-ca.pem", "test/root-ca.key");
+    let foobar_der = X509::from_pem(CERT).unwrap().to_der().unwrap();
+    let ca_der = X509::from_pem(ROOT_CERT).unwrap().to_der().unwrap();
+
+    let hosts = VirtualHosts::new();
+    hosts.insert("foobar.com", foobar.clone()).unwrap();
+    hosts.insert("*.example.com", ca.clone()).unwrap();
+    assert!(hosts.insert("", ca.clone()).is_err());
+    assert!(hosts.insert("*.*.example.com", ca.clone()).is_err());
+    assert!(hosts.insert("www.*.com", ca.clone()).is_err());
+
+    let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+    acceptor.set_virtual_hosts(hosts.clone());
+    let acceptor = acceptor.build();
+
+    assert_eq!(
+        vhost_handshake(&acceptor, "FOOBAR.com"),
+        Some((Some("foobar.com".to_string()), foobar_der.clone()))
+    );
+    assert_eq!(
+        vhost_handshake(&acceptor, "www.example.com"),
+        Some((Some("*.example.com".to_string()), ca_der.clone()))
+    );
+    assert_eq!(vhost_handshake(&acceptor, "a.b.example.com"), None);
+    assert_eq!(vhost_handshake(&acceptor, "example.com"), None);
+
+    // hosts can be changed while the acceptor is in use
+    assert!(hosts.insert("foobar.com", ca.clone()).unwrap().is_some());
+    hosts.set_default(Some(foobar.clone()));
+    assert_eq!(
+        vhost_handshake(&acceptor, "foobar.com"),
+        Some((Some("foobar.com".to_string()), ca_der.clone()))
+    );
+    assert_eq!(
+        vhost_handshake(&acceptor, "example.com"),
+        Some((None, foobar_der.clone()))
+    );
+
+    assert!(hosts.remove("*.example.com").is_some());
+    assert!(hosts.remove("*.example.com").is_none());
+    assert!(hosts.remove("").is_none());
+    assert!(hosts.remove("*.*.example.com").is_none());
+    assert_eq!(
+        vhost_handshake(&acceptor, "www.example.com"),
+        Some((None, foobar_der.clone()))
+    );
+}
+
 #[test]
 fn shutdown() {
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
@@ -1434,6 +1583,328 @@ fn new_session_callback() {
     guard.join().unwrap();
 }
 
+/// Performs a TLS 1.2 handshake against `server`, returning the client's session and whether it
+/// was resumed.
+#[cfg(any(ossl110, libressl261))]
+fn resume_handshake(server: &SslContext, session: Option<&SslSessionRef>) -> (SslSession, bool) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = server.clone();
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let ssl = Ssl::new(&server).unwrap();
+        let mut stream = ssl.accept(stream).unwrap();
+        stream.write_all(&[0]).unwrap();
+        stream.shutdown().unwrap();
+    });
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_max_proto_version(Some(SslVersion::TLS1_2)).unwrap();
+    let mut ssl = Ssl::new(&ctx.build()).unwrap();
+    if let Some(session) = session {
+        unsafe { ssl.set_session(session).unwrap() };
+    }
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut stream = ssl.connect(stream).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    guard.join().unwrap();
+
+    // sessions of connections which aren't shut down cleanly can't be resumed
+    stream.shutdown().unwrap();
+    let session = stream.ssl().session().unwrap().to_owned();
+    (session, stream.ssl().session_reused())
+}
+
+#[cfg(any(ossl110, libressl261))]
+fn ticket_server(keys: &SessionTicketKeys) -> SslContext {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_session_cache_mode(SslSessionCacheMode::OFF);
+    ctx.set_session_ticket_keys(keys.clone()).unwrap();
+    ctx.build()
+}
+
+#[test]
+#[cfg(any(ossl110, libressl261))]
+fn session_ticket_keys() {
+    let old = SessionTicketKey::generate().unwrap();
+    let keys = SessionTicketKeys::new(old.clone());
+    let server1 = ticket_server(&keys);
+    let server2 = ticket_server(&keys);
+
+    // a ticket issued by one server can be used with another sharing its keys
+    let (session, reused) = resume_handshake(&server1, None);
+    assert!(!reused);
+    let (_, reused) = resume_handshake(&server2, Some(&session));
+    assert!(reused);
+
+    // after a rotation the old ticket is still accepted, and renewed with the new key
+    keys.rotate(SessionTicketKey::generate().unwrap());
+    let (renewed, reused) = resume_handshake(&server2, Some(&session));
+    assert!(reused);
+
+    assert!(keys.retire(old.name()));
+    let (_, reused) = resume_handshake(&server1, Some(&session));
+    assert!(!reused);
+    let (_, reused) = resume_handshake(&server1, Some(&renewed));
+    assert!(reused);
+
+    // servers with different keys can't resume each other's sessions
+    let other = ticket_server(&SessionTicketKeys::new(SessionTicketKey::generate().unwrap()));
+    let (_, reused) = resume_handshake(&other, Some(&renewed));
+    assert!(!reused);
+}
+
+#[test]
+#[cfg(any(ossl110, libressl261))]
+fn ticket_key_callback() {
+    static RENEWED: AtomicBool = ATOMIC_BOOL_INIT;
+
+    let key = SessionTicketKey::generate().unwrap();
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_session_cache_mode(SslSessionCacheMode::OFF);
+    ctx.set_ticket_key_callback(move |_, request| match request {
+        TicketKeyRequest::Encrypt => Ok(TicketKeyResponse::Key(key.clone())),
+        TicketKeyRequest::Decrypt(name) => {
+            assert_eq!(name, key.name());
+            RENEWED.store(true, Ordering::SeqCst);
+            Ok(TicketKeyResponse::Renew(key.clone()))
+        }
+    }).unwrap();
+    let server = ctx.build();
+
+    let (session, _) = resume_handshake(&server, None);
+    let (renewed, reused) = resume_handshake(&server, Some(&session));
+    assert!(reused);
+    assert!(RENEWED.load(Ordering::SeqCst));
+    assert_ne!(session.to_der().unwrap(), renewed.to_der().unwrap());
+}
+
+#[test]
+#[cfg(any(ossl110, libressl261))]
+fn session_cache() {
+    let cache = Arc::new(MemorySessionCache::new(10));
+    let server = || {
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_options(SslOptions::NO_TICKET);
+        ctx.set_session_id_context(b"session_cache").unwrap();
+        ctx.set_session_cache(cache.clone());
+        ctx.build()
+    };
+    let server1 = server();
+    let server2 = server();
+
+    let (session, reused) = resume_handshake(&server1, None);
+    assert!(!reused);
+    assert_eq!(cache.len(), 1);
+    assert!(cache.get(session.id()).is_some());
+
+    let (_, reused) = resume_handshake(&server2, Some(&session));
+    assert!(reused);
+
+    cache.clear();
+    let (_, reused) = resume_handshake(&server2, Some(&session));
+    assert!(!reused);
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn session_introspection() {
+    let keys = SessionTicketKeys::new(SessionTicketKey::generate().unwrap());
+    let server = ticket_server(&keys);
+
+    let (session, reused) = resume_handshake(&server, None);
+    assert!(!reused);
+    assert_eq!(session.protocol_version(), SslVersion::TLS1_2);
+    assert!(session.cipher().is_some());
+    assert!(session.peer_certificate().is_some());
+    assert_eq!(session.hostname(), None);
+    assert_eq!(session.alpn_selected(), None);
+    assert!(session.has_ticket());
+    assert!(session.is_resumable());
+    assert!(session.time() > 0);
+    assert!(session.timeout() > 0);
+    let text = String::from_utf8(session.to_text().unwrap()).unwrap();
+    assert!(text.contains("TLSv1.2"));
+
+    let mut builder = SslSessionBuilder::from_session(&session).unwrap();
+    builder.set_time(1);
+    builder.set_timeout(60);
+    let copy = builder.build();
+    assert_eq!(copy.time(), 1);
+    assert_eq!(copy.timeout(), 60);
+    // the original session is unaffected
+    assert!(session.time() > 1);
+
+    let (session, reused) = resume_handshake(&server, Some(&session));
+    assert!(reused);
+
+    let mut builder = SslSessionBuilder::from_session(&session).unwrap();
+    builder.set_id_context(b"").unwrap();
+    assert!(builder.set_id_context(&[0; 33]).is_err());
+}
+
+#[test]
+#[cfg(any(ossl110, libressl261))]
+fn session_cache_stats() {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_options(SslOptions::NO_TICKET);
+    ctx.set_session_id_context(b"session_cache_stats").unwrap();
+    ctx.set_session_cache_size(10);
+    assert_eq!(ctx.set_session_cache_size(100), 10);
+    ctx.set_session_timeout(60);
+    assert_eq!(ctx.set_session_timeout(120), 60);
+    let ctx = ctx.build();
+    assert_eq!(ctx.session_cache_size(), 100);
+    assert_eq!(ctx.session_timeout(), 120);
+
+    let (session, reused) = resume_handshake(&ctx, None);
+    assert!(!reused);
+    let (_, reused) = resume_handshake(&ctx, Some(&session));
+    assert!(reused);
+
+    let stats = ctx.session_cache_stats();
+    assert_eq!(stats.number(), 1);
+    assert_eq!(stats.accept(), 2);
+    assert_eq!(stats.accept_good(), 2);
+    assert_eq!(stats.hits(), 1);
+    assert_eq!(stats.misses(), 0);
+
+    ctx.flush_sessions(session.time() + i64::from(session.timeout()) + 1);
+    assert_eq!(ctx.session_cache_stats().number, 0);
+
+    let (_, reused) = resume_handshake(&ctx, Some(&session));
+    assert!(!reused);
+    assert_eq!(ctx.session_cache_stats().misses, 1);
+}
+
+#[test]
+fn memory_session_cache() {
+    let cache = MemorySessionCache::new(2);
+    let timeout = Duration::from_secs(60);
+    cache.put(b"a", b"1".to_vec(), timeout);
+    cache.put(b"b", b"2".to_vec(), timeout);
+    assert_eq!(cache.get(b"a"), Some(b"1".to_vec()));
+
+    // "b" is the least recently used session
+    cache.put(b"c", b"3".to_vec(), timeout);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(b"b"), None);
+    assert_eq!(cache.get(b"a"), Some(b"1".to_vec()));
+    assert_eq!(cache.get(b"c"), Some(b"3".to_vec()));
+
+    cache.remove(b"a");
+    assert_eq!(cache.get(b"a"), None);
+
+    cache.put(b"d", b"4".to_vec(), Duration::from_secs(0));
+    assert_eq!(cache.get(b"d"), None);
+    assert_eq!(cache.len(), 1);
+}
+
+#[cfg(any(ossl110, libressl261))]
+fn connector_session_store(version: SslVersion) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let mut server = SslContext::builder(SslMethod::tls()).unwrap();
+    server
+        .set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    server
+        .set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    let server = server.build();
+    let guard = thread::spawn(move || {
+        for stream in listener.incoming().take(4) {
+            let ssl = Ssl::new(&server).unwrap();
+            let mut stream = ssl.accept(stream.unwrap()).unwrap();
+            stream.write_all(&[0]).unwrap();
+            stream.shutdown().unwrap();
+        }
+    });
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_max_proto_version(Some(version)).unwrap();
+    connector.set_session_store(10);
+    let connector = connector.build();
+
+    let connect_with = |port, verify_hostname| {
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut stream = connector
+            .configure()
+            .unwrap()
+            .verify_hostname(verify_hostname)
+            .connect("foobar.com", stream)
+            .unwrap();
+        // TLS 1.3 tickets are received after the handshake
+        stream.read_exact(&mut [0]).unwrap();
+        stream.shutdown().unwrap();
+        stream.ssl().session_reused()
+    };
+    let connect = |port| connect_with(port, true);
+
+    assert!(!connect(port));
+    assert!(connect(port));
+    assert!(connect(port));
+    assert!(connect(port));
+    guard.join().unwrap();
+
+    // a server on another port which does not know the session performs a full handshake
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = {
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.build()
+    };
+    let guard = thread::spawn(move || {
+        for stream in listener.incoming().take(4) {
+            let mut stream = Ssl::new(&server).unwrap().accept(stream.unwrap()).unwrap();
+            stream.write_all(&[0]).unwrap();
+            stream.shutdown().unwrap();
+        }
+    });
+    assert!(!connect(port));
+    // sessions are neither stored from nor used by connections without hostname verification
+    assert!(!connect_with(port, false));
+    assert!(!connect_with(port, false));
+    assert!(connect(port));
+    guard.join().unwrap();
+}
+
+#[test]
+#[cfg(any(ossl110, libressl261))]
+fn connector_session_store_tls12() {
+    connector_session_store(SslVersion::TLS1_2);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn connector_session_store_tls13() {
+    connector_session_store(SslVersion::TLS1_3);
+}
+
 #[test]
 fn keying_export() {
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
@@ -1839,3 +2310,1313 @@ fn client_hello() {
 
     guard.join().unwrap();
 }
+
+#[test]
+#[cfg(ossl111)]
+fn client_hello_ja3() {
+    use ssl::{ClientHelloResponse, Ja3Fingerprint};
+
+    let server_ja3 = Arc::new(Mutex::new(None));
+    let client_ja3s = Arc::new(Mutex::new(None));
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    let server_ja3_2 = server_ja3.clone();
+    ctx.set_client_hello_callback(move |ssl, _| {
+        let extensions = ssl.client_hello_extensions_present().unwrap();
+        assert!(extensions.contains(&10));
+        assert!(extensions.contains(&43));
+        assert_eq!(ssl.client_hello_ext(0), None);
+        assert!(ssl.client_hello_ext(43).is_some());
+        assert!(ssl.client_hello_supported_groups().unwrap().contains(&29));
+        assert!(ssl.client_hello_ec_point_formats().unwrap().contains(&0));
+        assert!(ssl
+            .client_hello_supported_versions()
+            .unwrap()
+            .contains(&0x0304));
+        assert!(ssl
+            .client_hello_signature_algorithms()
+            .unwrap()
+            .contains(&0x0804));
+
+        *server_ja3_2.lock().unwrap() = ssl.client_hello_ja3();
+        Ok(ClientHelloResponse::SUCCESS)
+    });
+    let ctx = ctx.build();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut stream = Ssl::new(&ctx).unwrap().accept(stream).unwrap();
+        stream.write_all(&[0]).unwrap();
+    });
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    let client_ja3s2 = client_ja3s.clone();
+    ctx.set_msg_callback(move |_, message| {
+        if message.handshake_type() == Some(2) {
+            *client_ja3s2.lock().unwrap() = Ja3Fingerprint::from_server_hello(message.data());
+        }
+    });
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+    let mut stream = ssl.connect(TcpStream::connect(addr).unwrap()).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    guard.join().unwrap();
+
+    let ja3 = server_ja3.lock().unwrap().take().unwrap();
+    assert!(ja3.as_str().starts_with("771,"));
+    assert_eq!(ja3.as_str().split(',').count(), 5);
+    assert_eq!(ja3.md5().unwrap().len(), 32);
+
+    let ja3s = client_ja3s.lock().unwrap().take().unwrap();
+    assert!(ja3s.as_str().starts_with("771,"));
+    assert!(ja3s.as_str().ends_with("43-51") || ja3s.as_str().ends_with("51-43"));
+
+    let ja3 = Ja3Fingerprint::client(771, &[0x0a0a, 4865, 4866], &[0, 10], &[29, 23], &[0]);
+    assert_eq!(ja3.to_string(), "771,4865-4866,0-10,29-23,0");
+    assert_eq!(ja3.md5().unwrap(), "b57e61390dded8e2aaeff3ad22d89e36");
+}
+
+#[test]
+#[cfg(any(ossl102, libressl261))]
+fn parse_client_hello() {
+    use ssl::ClientHello;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_alpn_protos(b"\x02h2\x08http/1.1").unwrap();
+        let mut ssl = Ssl::new(&ctx.build()).unwrap();
+        ssl.set_hostname("foobar.com").unwrap();
+        let _ = ssl.connect(TcpStream::connect(addr).unwrap());
+    });
+
+    let mut stream = listener.accept().unwrap().0;
+    let mut buf = vec![];
+    let hello = loop {
+        let mut chunk = [0; 1024];
+        let len = stream.read(&mut chunk).unwrap();
+        assert!(len > 0);
+        buf.extend_from_slice(&chunk[..len]);
+        if let Some(hello) = ClientHello::parse(&buf).unwrap() {
+            break hello;
+        }
+    };
+    drop(stream);
+    guard.join().unwrap();
+
+    assert_eq!(hello.legacy_version(), SslVersion::TLS1_2);
+    assert_eq!(hello.random().len(), 32);
+    assert_eq!(hello.server_name(), Some("foobar.com"));
+    assert_eq!(
+        hello.alpn_protocols(),
+        Some(vec![&b"h2"[..], &b"http/1.1"[..]])
+    );
+    assert!(hello.extensions_present().contains(&0));
+    assert!(hello.ciphers().len() >= 2);
+    assert!(hello.supported_groups().is_some());
+    assert!(hello.signature_algorithms().is_some());
+    assert!(hello.ja3().as_str().starts_with("771,"));
+
+    for len in 0..buf.len() {
+        assert!(ClientHello::parse(&buf[..len]).unwrap().is_none());
+    }
+
+    let body = &buf[5..];
+    let mut split = vec![];
+    for fragment in body.chunks(body.len() / 2 + 1) {
+        split.extend_from_slice(&[22, 3, 1, (fragment.len() >> 8) as u8, fragment.len() as u8]);
+        split.extend_from_slice(fragment);
+    }
+    let hello = ClientHello::parse(&split).unwrap().unwrap();
+    assert_eq!(hello.server_name(), Some("foobar.com"));
+
+    assert!(ClientHello::parse(b"GET / HTTP/1.1\r\n").is_err());
+}
+
+#[test]
+#[cfg(ossl111)]
+fn post_handshake_auth_and_key_update() {
+    use ssl::SslKeyUpdateType;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_ca_file(&Path::new("test/root-ca.pem")).unwrap();
+        ctx.set_verify(SslVerifyMode::PEER | SslVerifyMode::POST_HANDSHAKE);
+        let ssl = Ssl::new(&ctx.build()).unwrap();
+        let mut stream = ssl.accept(stream).unwrap();
+        assert_eq!(stream.ssl().version2(), Some(SslVersion::TLS1_3));
+
+        stream.read_exact(&mut [0]).unwrap();
+        assert!(stream.ssl().peer_certificate().is_none());
+
+        stream.verify_client_post_handshake().unwrap();
+        stream.write_all(&[1]).unwrap();
+        stream.read_exact(&mut [0]).unwrap();
+        assert!(stream.ssl().peer_certificate().is_some());
+        assert_eq!(stream.ssl().verify_result(), X509VerifyResult::OK);
+
+        stream.key_update(SslKeyUpdateType::REQUESTED).unwrap();
+        assert_eq!(stream.ssl().key_update_type(), SslKeyUpdateType::NONE);
+        stream.write_all(&[2]).unwrap();
+        stream.read_exact(&mut [0]).unwrap();
+    });
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_post_handshake_auth(true);
+    let mut ssl = Ssl::new(&ctx.build()).unwrap();
+    ssl.key_update(SslKeyUpdateType::NOT_REQUESTED).unwrap_err();
+    let mut stream = ssl.connect(stream).unwrap();
+
+    stream.write_all(&[0]).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    stream.write_all(&[0]).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    stream.write_all(&[0]).unwrap();
+
+    guard.join().unwrap();
+}
+
+#[test]
+#[cfg(ossl111)]
+fn psk_sessions() {
+    const PSK: &[u8] = b"thisisaverysecurekeythisisaveryx";
+    const IDENTITY: &[u8] = b"device-1";
+    const TLS_AES_128_GCM_SHA256: [u8; 2] = [0x13, 0x01];
+    static CLIENT_CALLED: AtomicBool = ATOMIC_BOOL_INIT;
+    static SERVER_CALLED: AtomicBool = ATOMIC_BOOL_INIT;
+
+    fn psk_session(ssl: &SslRef) -> SslSession {
+        let mut session = SslSessionBuilder::new().unwrap();
+        session.set_master_key(PSK).unwrap();
+        session
+            .set_cipher(ssl.find_cipher(TLS_AES_128_GCM_SHA256).unwrap())
+            .unwrap();
+        session.set_protocol_version(SslVersion::TLS1_3).unwrap();
+        session.build()
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.use_psk_identity_hint("devices").unwrap();
+        ctx.set_psk_find_session_callback(|ssl, identity| {
+            SERVER_CALLED.store(true, Ordering::SeqCst);
+            if identity == IDENTITY {
+                Ok(Some(psk_session(ssl)))
+            } else {
+                Ok(None)
+            }
+        });
+        let mut ssl = Ssl::new(&ctx.build()).unwrap();
+        ssl.use_psk_identity_hint("device").unwrap();
+        let mut stream = ssl.accept(stream).unwrap();
+        assert!(stream.ssl().peer_certificate().is_none());
+        stream.write_all(&[0]).unwrap();
+    });
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_ciphersuites("TLS_AES_128_GCM_SHA256").unwrap();
+    ctx.set_psk_use_session_callback(|ssl, _| {
+        CLIENT_CALLED.store(true, Ordering::SeqCst);
+        Ok(Some((IDENTITY.to_vec(), psk_session(ssl))))
+    });
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+    let mut stream = ssl.connect(stream).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+
+    assert_eq!(stream.ssl().version2(), Some(SslVersion::TLS1_3));
+    assert_eq!(
+        stream.ssl().current_cipher().unwrap().name(),
+        "TLS_AES_128_GCM_SHA256"
+    );
+    assert!(CLIENT_CALLED.load(Ordering::SeqCst) && SERVER_CALLED.load(Ordering::SeqCst));
+
+    guard.join().unwrap();
+}
+
+#[test]
+fn psk_identity_hint() {
+    const CIPHER: &'static str = "PSK-AES128-CBC-SHA";
+    const PSK: &[u8] = b"thisisaverysecurekey";
+    const CLIENT_IDENT: &[u8] = b"thisisaclient";
+    static CLIENT_CALLED: AtomicBool = ATOMIC_BOOL_INIT;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_cipher_list(CIPHER).unwrap();
+        ctx.use_psk_identity_hint("devices").unwrap();
+        ctx.set_psk_server_callback(move |_, identity, psk| {
+            assert!(identity.unwrap_or(&[]) == CLIENT_IDENT);
+            psk[..PSK.len()].copy_from_slice(&PSK);
+            Ok(PSK.len())
+        });
+        let mut ssl = Ssl::new(&ctx.build()).unwrap();
+        ssl.use_psk_identity_hint("device").unwrap();
+        ssl.accept(stream).unwrap();
+    });
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    #[cfg(ossl111)]
+    {
+        ctx.set_options(super::SslOptions {
+            bits: ::ffi::SSL_OP_NO_TLSv1_3,
+        });
+    }
+    ctx.set_cipher_list(CIPHER).unwrap();
+    ctx.set_psk_client_callback(move |_, hint, identity, psk| {
+        assert_eq!(hint, Some(&b"device"[..]));
+        identity[..CLIENT_IDENT.len()].copy_from_slice(&CLIENT_IDENT);
+        identity[CLIENT_IDENT.len()] = 0;
+        psk[..PSK.len()].copy_from_slice(&PSK);
+        CLIENT_CALLED.store(true, Ordering::SeqCst);
+        Ok(PSK.len())
+    });
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+    ssl.connect(stream).unwrap();
+
+    assert!(CLIENT_CALLED.load(Ordering::SeqCst));
+
+    guard.join().unwrap();
+}
+
+#[test]
+#[cfg(ossl111)]
+fn cert_callback() {
+    use ssl::{CertCallbackResponse, NameType};
+
+    static RETRIED: AtomicBool = ATOMIC_BOOL_INIT;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_cert_callback(|ssl| {
+            assert_eq!(ssl.servername(NameType::HOST_NAME), Some("foobar.com"));
+            assert!(!ssl.peer_signature_algorithms().is_empty());
+            assert!(ssl.client_ciphers().map_or(0, |c| c.len()) > 0);
+
+            // simulate an asynchronous lookup
+            if !RETRIED.swap(true, Ordering::SeqCst) {
+                return Ok(CertCallbackResponse::RETRY);
+            }
+
+            let cert = include_bytes!("../../test/cert.pem");
+            let cert = X509::from_pem(cert).unwrap();
+            let key = include_bytes!("../../test/key.pem");
+            let key = PKey::private_key_from_pem(key).unwrap();
+            ssl.set_certificate(&cert)?;
+            ssl.set_private_key(&key)?;
+            Ok(CertCallbackResponse::SUCCESS)
+        });
+
+        let ssl = Ssl::new(&ctx.build()).unwrap();
+        let mut stream = match ssl.accept(stream) {
+            Err(HandshakeError::Failure(mid)) => {
+                assert_eq!(mid.error().code(), ErrorCode::WANT_X509_LOOKUP);
+                mid.handshake().unwrap()
+            }
+            _ => panic!("expected the certificate callback to retry"),
+        };
+        stream.write_all(&[0]).unwrap();
+    });
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    let mut ssl = Ssl::new(&ctx.build()).unwrap();
+    ssl.set_hostname("foobar.com").unwrap();
+
+    let mut stream = ssl.connect(stream).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    assert!(stream.ssl().peer_certificate().is_some());
+
+    guard.join().unwrap();
+}
+
+#[test]
+fn client_cert_callback() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_verify_callback(
+            SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT,
+            |_, _| true,
+        );
+
+        let ssl = Ssl::new(&ctx.build()).unwrap();
+        let mut stream = ssl.accept(stream).unwrap();
+        assert!(stream.ssl().peer_certificate().is_some());
+        stream.write_all(&[0]).unwrap();
+    });
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_client_cert_callback(|_| {
+        let cert = include_bytes!("../../test/cert.pem");
+        let key = include_bytes!("../../test/key.pem");
+        Some((
+            X509::from_pem(cert).unwrap(),
+            PKey::private_key_from_pem(key).unwrap(),
+        ))
+    });
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+
+    let mut stream = ssl.connect(stream).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+
+    guard.join().unwrap();
+}
+
+#[cfg(ossl111)]
+fn override_handshake<F>(configure: F) -> SslStream<TcpStream>
+where
+    F: FnOnce(&mut SslRef),
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        let ssl = Ssl::new(&ctx.build()).unwrap();
+        let mut stream = ssl.accept(stream).unwrap();
+        stream.write_all(&[0]).unwrap();
+    });
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    configure(&mut ssl);
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut stream = ssl.connect(stream).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    guard.join().unwrap();
+    stream
+}
+
+#[test]
+#[cfg(ossl111)]
+fn ssl_overrides() {
+    let stream = override_handshake(|ssl| {
+        ssl.set_max_proto_version(Some(SslVersion::TLS1_2)).unwrap();
+        assert_eq!(ssl.max_proto_version(), Some(SslVersion::TLS1_2));
+        ssl.set_cipher_list("ECDHE-RSA-AES128-GCM-SHA256").unwrap();
+    });
+    assert_eq!(stream.ssl().version2(), Some(SslVersion::TLS1_2));
+    assert_eq!(
+        stream.ssl().current_cipher().unwrap().name(),
+        "ECDHE-RSA-AES128-GCM-SHA256"
+    );
+
+    let stream = override_handshake(|ssl| {
+        ssl.set_min_proto_version(Some(SslVersion::TLS1_3)).unwrap();
+        ssl.set_ciphersuites("TLS_CHACHA20_POLY1305_SHA256").unwrap();
+        ssl.set_options(SslOptions::NO_TICKET);
+        assert!(ssl.options().contains(SslOptions::NO_TICKET));
+        ssl.clear_options(SslOptions::NO_TICKET);
+        assert!(!ssl.options().contains(SslOptions::NO_TICKET));
+
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(X509::from_pem(ROOT_CERT).unwrap()).unwrap();
+        ssl.set_verify_cert_store(store.build()).unwrap();
+        ssl.set_verify(SslVerifyMode::PEER);
+        ssl.set_verify_depth(2);
+    });
+    assert_eq!(stream.ssl().version2(), Some(SslVersion::TLS1_3));
+    assert_eq!(
+        stream.ssl().current_cipher().unwrap().name(),
+        "TLS_CHACHA20_POLY1305_SHA256"
+    );
+    assert_eq!(stream.ssl().verify_result(), X509VerifyResult::OK);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn multiple_certificates() {
+    use asn1::Asn1Time;
+    use ec::{EcGroup, EcKey};
+    use nid::Nid;
+    use ssl::SslBuildChainFlags;
+    use x509::X509Builder;
+
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let ec_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "ecdsa.foobar.com")
+        .unwrap();
+    let name = name.build();
+    let mut ec_cert = X509Builder::new().unwrap();
+    ec_cert.set_version(2).unwrap();
+    ec_cert.set_subject_name(&name).unwrap();
+    ec_cert.set_issuer_name(&name).unwrap();
+    ec_cert.set_pubkey(&ec_key).unwrap();
+    ec_cert
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    ec_cert
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    ec_cert.sign(&ec_key, MessageDigest::sha256()).unwrap();
+    let ec_cert = ec_cert.build();
+
+    let rsa_cert = X509::from_pem(CERT).unwrap();
+    let root = X509::from_pem(ROOT_CERT).unwrap();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate(&rsa_cert).unwrap();
+    ctx.set_private_key(&PKey::private_key_from_pem(KEY).unwrap())
+        .unwrap();
+    ctx.add_chain_cert(&root).unwrap();
+    ctx.set_certificate(&ec_cert).unwrap();
+    ctx.set_private_key(&ec_key).unwrap();
+
+    let certs = ctx.certificates();
+    assert_eq!(certs.len(), 2);
+    assert_eq!(certs[0].certificate().to_der().unwrap(), rsa_cert.to_der().unwrap());
+    assert_eq!(certs[0].chain().len(), 1);
+    assert_eq!(certs[1].certificate().to_der().unwrap(), ec_cert.to_der().unwrap());
+    assert!(certs[1].chain().is_empty());
+    // enumeration leaves the ECDSA certificate current
+    ctx.clear_chain_certs().unwrap();
+    assert!(ctx.certificates()[1].chain().is_empty());
+    assert_eq!(ctx.certificates()[0].chain().len(), 1);
+
+    ctx.select_current_cert(&rsa_cert).unwrap();
+    ctx.cert_store_mut().add_cert(root.clone()).unwrap();
+    ctx.build_cert_chain(SslBuildChainFlags::NO_ROOT).unwrap();
+    assert!(ctx.certificates()[0].chain().is_empty());
+    ctx.build_cert_chain(SslBuildChainFlags::empty()).unwrap();
+    assert_eq!(ctx.certificates()[0].chain().len(), 1);
+    assert!(ctx.select_current_cert(&root).is_err());
+    let ctx = ctx.build();
+
+    for &(cipher, cert) in &[
+        ("ECDHE-RSA-AES128-GCM-SHA256", &rsa_cert),
+        ("ECDHE-ECDSA-AES128-GCM-SHA256", &ec_cert),
+    ] {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server_ctx = ctx.clone();
+        let guard = thread::spawn(move || {
+            let stream = listener.accept().unwrap().0;
+            let mut stream = Ssl::new(&server_ctx).unwrap().accept(stream).unwrap();
+            stream.write_all(&[0]).unwrap();
+        });
+
+        let mut client = SslContext::builder(SslMethod::tls()).unwrap();
+        client.set_max_proto_version(Some(SslVersion::TLS1_2)).unwrap();
+        client.set_cipher_list(cipher).unwrap();
+        let ssl = Ssl::new(&client.build()).unwrap();
+        let mut stream = ssl.connect(TcpStream::connect(addr).unwrap()).unwrap();
+        stream.read_exact(&mut [0]).unwrap();
+        guard.join().unwrap();
+
+        assert_eq!(
+            stream.ssl().peer_certificate().unwrap().to_der().unwrap(),
+            cert.to_der().unwrap()
+        );
+    }
+
+    // a current certificate without a private key is restored as well
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate(&rsa_cert).unwrap();
+    ctx.set_private_key(&PKey::private_key_from_pem(KEY).unwrap())
+        .unwrap();
+    ctx.set_certificate(&ec_cert).unwrap();
+    assert_eq!(ctx.certificates().len(), 1);
+    let ctx = ctx.build();
+    assert_eq!(
+        ctx.certificate().unwrap().to_der().unwrap(),
+        ec_cert.to_der().unwrap()
+    );
+    assert!(ctx.private_key().is_none());
+}
+
+#[test]
+#[cfg(ossl111)]
+fn groups_and_sigalgs() {
+    use nid::Nid;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_groups_list("X25519").unwrap();
+        ctx.set_sigalgs_list("RSA-PSS+SHA256:RSA-PSS+SHA384").unwrap();
+        let ssl = Ssl::new(&ctx.build()).unwrap();
+        let mut stream = ssl.accept(stream).unwrap();
+        stream.write_all(&[0]).unwrap();
+
+        assert_eq!(stream.ssl().negotiated_group(), Some(Nid::X25519));
+        let shared = stream.ssl().shared_signature_algorithms();
+        assert_eq!(
+            shared.iter().map(|alg| alg.code()).collect::<Vec<_>>(),
+            vec![0x0805, 0x0804]
+        );
+        assert_eq!(shared[0].digest(), Nid::SHA384);
+    });
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    assert!(ctx.set_groups_list("not-a-group").is_err());
+    assert!(ctx.set_sigalgs_list("not-a-sigalg").is_err());
+    ctx.set_groups_list("P-256:X25519").unwrap();
+    ctx.set_sigalgs_list("RSA-PSS+SHA384:RSA-PSS+SHA256:ECDSA+SHA256")
+        .unwrap();
+    ctx.set_client_sigalgs_list("ECDSA+SHA256").unwrap();
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+    let mut stream = ssl.connect(TcpStream::connect(addr).unwrap()).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    guard.join().unwrap();
+
+    // the server's preference wins, so the client must retry with an X25519 key share
+    assert_eq!(stream.ssl().negotiated_group(), Some(Nid::X25519));
+    assert_eq!(
+        stream.ssl().peer_tmp_key().unwrap().id().as_raw(),
+        Nid::X25519.as_raw()
+    );
+    assert_eq!(stream.ssl().peer_signature_type(), Some(Nid::RSASSAPSS));
+    assert_eq!(stream.ssl().peer_signature_digest(), Some(Nid::SHA384));
+}
+
+#[test]
+#[cfg(ossl111)]
+fn handshake_info() {
+    use nid::Nid;
+    use ssl::SslEarlyDataStatus;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_alpn_select_callback(|_, client| {
+            ssl::select_next_proto(b"\x02h2", client).ok_or(ssl::AlpnError::NOACK)
+        });
+        let ssl = Ssl::new(&ctx.build()).unwrap();
+        let mut stream = ssl.accept(stream).unwrap();
+        stream.write_all(&[0]).unwrap();
+
+        let info = stream.ssl().handshake_info();
+        assert_eq!(info.server_name(), Some("foobar.com"));
+        assert_eq!(info.peer_subject(), None);
+        assert!(info.duration().is_none());
+    });
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_max_proto_version(Some(SslVersion::TLS1_2)).unwrap();
+    ctx.set_cipher_list("ECDHE-RSA-AES128-GCM-SHA256").unwrap();
+    ctx.set_alpn_protos(b"\x02h2").unwrap();
+    ctx.set_handshake_timing(true);
+    let mut ssl = Ssl::new(&ctx.build()).unwrap();
+    ssl.set_hostname("foobar.com").unwrap();
+    let mut stream = ssl.connect(TcpStream::connect(addr).unwrap()).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    guard.join().unwrap();
+
+    let info = stream.ssl().handshake_info();
+    assert_eq!(info.version(), Some(SslVersion::TLS1_2));
+    assert_eq!(info.version_str(), "TLSv1.2");
+    assert_eq!(info.cipher(), Some("ECDHE-RSA-AES128-GCM-SHA256"));
+    assert_eq!(
+        info.cipher_standard_name(),
+        Some("TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256")
+    );
+    assert_eq!(info.group(), Some(Nid::X25519));
+    assert_eq!(info.alpn_protocol(), Some(&b"h2"[..]));
+    assert_eq!(info.server_name(), Some("foobar.com"));
+    assert!(!info.session_reused());
+    assert_eq!(info.early_data_status(), SslEarlyDataStatus::NOT_SENT);
+    assert_eq!(
+        info.peer_subject(),
+        Some("C=AU, ST=Some-State, O=Internet Widgits Pty Ltd, CN=foobar.com")
+    );
+    let cert = X509::from_pem(CERT).unwrap();
+    assert_eq!(
+        info.peer_fingerprint(),
+        Some(&*cert.digest(MessageDigest::sha256()).unwrap())
+    );
+    assert!(info.duration().is_some());
+
+    let line = info.to_string();
+    assert!(
+        line.starts_with(
+            "protocol=TLSv1.2 cipher=ECDHE-RSA-AES128-GCM-SHA256 \
+             cipher_std=TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256 group=X25519"
+        ),
+        "{}",
+        line
+    );
+    assert!(line.contains(" alpn=h2 sni=foobar.com resumed=false early_data=not_sent"));
+    assert!(line.contains(", CN=foobar.com\" peer_sha256="));
+    assert!(line.contains(" duration_ms="));
+}
+
+#[test]
+fn msg_and_info_callbacks() {
+    use ssl::{MessageDirection, SslAlert, SslContentType, SslInfoEvent};
+
+    let messages = Arc::new(Mutex::new(vec![]));
+    let events = Arc::new(Mutex::new(vec![]));
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    let messages2 = messages.clone();
+    ctx.set_msg_callback(move |_, message| {
+        messages2.lock().unwrap().push((
+            message.direction(),
+            message.content_type(),
+            message.handshake_type(),
+            message.data().len(),
+        ));
+    });
+    let events2 = events.clone();
+    ctx.set_info_callback(move |ssl, event| {
+        assert!(ssl.is_server());
+        events2.lock().unwrap().push(event);
+    });
+    let ctx = ctx.build();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut stream = Ssl::new(&ctx).unwrap().accept(stream).unwrap();
+        stream.write_all(&[0]).unwrap();
+        assert_eq!(stream.read(&mut [0]).unwrap(), 0);
+    });
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let ssl = Ssl::new(&ctx).unwrap();
+    let mut stream = ssl.connect(TcpStream::connect(addr).unwrap()).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    stream.shutdown().unwrap();
+    guard.join().unwrap();
+
+    let messages = messages.lock().unwrap();
+    let handshake = messages
+        .iter()
+        .filter(|m| m.1 == SslContentType::HANDSHAKE)
+        .collect::<Vec<_>>();
+    assert_eq!(handshake[0].0, MessageDirection::Received);
+    assert_eq!(handshake[0].2, Some(1));
+    assert_eq!(handshake[1].0, MessageDirection::Sent);
+    assert_eq!(handshake[1].2, Some(2));
+    assert!(handshake.iter().all(|m| m.3 >= 4));
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.0 == MessageDirection::Received && m.1 == SslContentType::ALERT)
+    );
+
+    let events = events.lock().unwrap();
+    assert_eq!(events[0], SslInfoEvent::HandshakeStart);
+    assert!(events.contains(&SslInfoEvent::StateChange));
+    assert!(events.contains(&SslInfoEvent::HandshakeDone));
+    assert!(events.contains(&SslInfoEvent::Alert {
+        direction: MessageDirection::Received,
+        fatal: false,
+        alert: SslAlert::CLOSE_NOTIFY,
+    }));
+    assert_eq!(SslAlert::CLOSE_NOTIFY.description(), "close notify");
+}
+
+#[test]
+fn spiffe_id() {
+    let id = SpiffeId::from_uri("spiffe://example.org/ns/prod/sa/web").unwrap();
+    assert_eq!(id.trust_domain(), "example.org");
+    assert_eq!(id.path(), "/ns/prod/sa/web");
+    assert_eq!(id.to_string(), "spiffe://example.org/ns/prod/sa/web");
+
+    let id = SpiffeId::from_uri("spiffe://example.org").unwrap();
+    assert_eq!(id.trust_domain(), "example.org");
+    assert_eq!(id.path(), "");
+
+    for uri in &[
+        "https://example.org/service",
+        "spiffe://",
+        "spiffe:///service",
+        "spiffe://Example.org/service",
+        "spiffe://example.org:8080/service",
+        "spiffe://user@example.org/service",
+        "spiffe://example.org/",
+        "spiffe://example.org//service",
+        "spiffe://example.org/../service",
+        "spiffe://example.org/service?query",
+        "spiffe://example.org/service#fragment",
+    ] {
+        assert!(SpiffeId::from_uri(uri).is_none(), "{}", uri);
+    }
+}
+
+fn spiffe_server(listener: TcpListener) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let key = PKey::private_key_from_pem(KEY).unwrap();
+        let cert = X509::from_pem(SPIFFE_CERT).unwrap();
+        let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+        acceptor.set_private_key(&key).unwrap();
+        acceptor.set_certificate(&cert).unwrap();
+        let acceptor = acceptor.build();
+        let stream = listener.accept().unwrap().0;
+        if let Ok(mut stream) = acceptor.accept(stream) {
+            stream.write_all(b"hello").unwrap();
+        }
+    })
+}
+
+#[test]
+fn spiffe_connector_bundle() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let t = spiffe_server(listener);
+
+    let mut bundle = X509StoreBuilder::new().unwrap();
+    bundle.add_cert(X509::from_pem(ROOT_CERT).unwrap()).unwrap();
+
+    let mut verifier = SpiffeVerifier::new();
+    verifier.allow_id(SpiffeId::from_uri("spiffe://example.org/service").unwrap());
+    verifier.add_bundle("example.org", bundle.build());
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_spiffe_verifier(verifier);
+    let connector = connector.build();
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut stream = connector
+        .configure()
+        .unwrap()
+        .verify_hostname(false)
+        .connect("foobar.com", stream)
+        .unwrap();
+
+    let id = stream.ssl().spiffe_id().unwrap();
+    assert_eq!(id.as_str(), "spiffe://example.org/service");
+
+    let mut buf = [0; 5];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(b"hello", &buf);
+
+    t.join().unwrap();
+}
+
+#[test]
+fn spiffe_connector_bundle_hostname_mismatch() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let t = spiffe_server(listener);
+
+    let mut bundle = X509StoreBuilder::new().unwrap();
+    bundle.add_cert(X509::from_pem(ROOT_CERT).unwrap()).unwrap();
+
+    let mut verifier = SpiffeVerifier::new();
+    verifier.add_bundle("example.org", bundle.build());
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_spiffe_verifier(verifier);
+    let connector = connector.build();
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let r = connector.connect("foobar.com", stream);
+    assert!(r.is_err());
+
+    t.join().unwrap();
+}
+
+#[test]
+fn spiffe_connector_rejects_id() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let t = spiffe_server(listener);
+
+    let mut verifier = SpiffeVerifier::new();
+    verifier.allow_trust_domain("example.com");
+    verifier.set_predicate(|id| id.path() == "/other");
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_spiffe_verifier(verifier);
+    let connector = connector.build();
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let r = connector
+        .configure()
+        .unwrap()
+        .verify_hostname(false)
+        .connect("foobar.com", stream);
+    assert!(r.is_err());
+
+    t.join().unwrap();
+}
+
+#[test]
+fn spiffe_acceptor() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let t = thread::spawn(move || {
+        let key = PKey::private_key_from_pem(KEY).unwrap();
+        let cert = X509::from_pem(CERT).unwrap();
+        let mut verifier = SpiffeVerifier::new();
+        verifier.set_predicate(|id| id.trust_domain() == "example.org");
+
+        let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+        acceptor.set_private_key(&key).unwrap();
+        acceptor.set_certificate(&cert).unwrap();
+        acceptor.set_ca_file("test/root-ca.pem").unwrap();
+        acceptor.set_spiffe_verifier(verifier);
+        let acceptor = acceptor.build();
+
+        let stream = listener.accept().unwrap().0;
+        let mut stream = acceptor.accept(stream).unwrap();
+        let id = stream.ssl().spiffe_id().unwrap();
+        assert_eq!(id.path(), "/service");
+
+        stream.write_all(b"hello").unwrap();
+    });
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector
+        .set_private_key(&PKey::private_key_from_pem(KEY).unwrap())
+        .unwrap();
+    connector
+        .set_certificate(&X509::from_pem(SPIFFE_CERT).unwrap())
+        .unwrap();
+    let connector = connector.build();
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut stream = connector.connect("foobar.com", stream).unwrap();
+    assert!(stream.ssl().spiffe_id().is_none());
+
+    let mut buf = [0; 5];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(b"hello", &buf);
+
+    t.join().unwrap();
+}
+
+#[test]
+fn engine() {
+    fn transfer(from: &mut SslEngine, to: &mut SslEngine) {
+        let mut buf = [0; 1024];
+        while from.wants_write() {
+            let n = from.write_tls(&mut buf);
+            to.read_tls(&buf[..n]).unwrap();
+        }
+    }
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    let mut server = SslEngine::new(Ssl::new(&ctx.build()).unwrap()).unwrap();
+    server.set_accept_state();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_ca_file("test/root-ca.pem").unwrap();
+    ctx.set_verify(SslVerifyMode::PEER);
+    let mut client = SslEngine::new(Ssl::new(&ctx.build()).unwrap()).unwrap();
+    client.set_connect_state();
+
+    assert!(!client.wants_write());
+    assert_eq!(client.handshake().unwrap_err().code(), ErrorCode::WANT_READ);
+    assert!(client.wants_read());
+    assert!(client.wants_write());
+
+    loop {
+        transfer(&mut client, &mut server);
+        let s = server.handshake();
+        transfer(&mut server, &mut client);
+        let c = client.handshake();
+        match (c, s) {
+            (Ok(()), Ok(())) => break,
+            (c, s) => {
+                for r in vec![c, s] {
+                    if let Err(e) = r {
+                        assert_eq!(e.code(), ErrorCode::WANT_READ);
+                    }
+                }
+            }
+        }
+    }
+    assert_eq!(client.ssl().verify_result(), X509VerifyResult::OK);
+
+    let mut buf = [0; 5];
+    assert_eq!(server.read(&mut buf).unwrap_err().code(), ErrorCode::WANT_READ);
+    assert_eq!(client.write(b"hello").unwrap(), 5);
+    transfer(&mut client, &mut server);
+    assert_eq!(server.read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+
+    assert_eq!(client.shutdown().unwrap(), ShutdownResult::Sent);
+    transfer(&mut client, &mut server);
+    assert_eq!(server.read(&mut buf).unwrap_err().code(), ErrorCode::ZERO_RETURN);
+    assert_eq!(server.shutdown().unwrap(), ShutdownResult::Received);
+}
+
+#[test]
+#[cfg(ossl110)]
+fn dtls_listener() {
+    let mut ctx = SslContext::builder(SslMethod::dtls()).unwrap();
+    ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_cookie_generate_cb(|_, buf| {
+        buf[..6].copy_from_slice(b"cookie");
+        Ok(6)
+    });
+    ctx.set_cookie_verify_cb(|_, cookie| cookie == b"cookie");
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let mut listener = DtlsListener::new(ctx.build(), socket);
+    listener.set_mtu(500);
+    let addr = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let mut threads = vec![];
+        for _ in 0..2 {
+            let stream = listener.accept().unwrap();
+            threads.push(thread::spawn(move || {
+                let mut stream = stream.handshake().unwrap();
+                let mut buf = [0; 100];
+                let n = stream.recv(&mut buf).unwrap();
+                assert_eq!(&buf[..n], b"hello");
+                let n = stream.recv(&mut buf).unwrap();
+                assert_eq!(&buf[..n], b"world");
+                stream.send(b"bye").unwrap();
+            }));
+        }
+        for t in threads {
+            t.join().unwrap();
+        }
+    });
+
+    let clients = (0..2)
+        .map(|_| {
+            thread::spawn(move || {
+                let mut ctx = SslContext::builder(SslMethod::dtls()).unwrap();
+                ctx.set_ca_file("test/root-ca.pem").unwrap();
+                ctx.set_verify(SslVerifyMode::PEER);
+                let ssl = Ssl::new(&ctx.build()).unwrap();
+
+                let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+                let mut stream = DtlsStream::connect(ssl, socket, addr).unwrap();
+                assert_eq!(stream.peer_addr(), addr);
+                assert!(stream.ssl().dtls_timeout().is_none());
+
+                stream.send(b"hello").unwrap();
+                stream.send(b"world").unwrap();
+
+                let mut buf = [0; 100];
+                let n = stream.recv(&mut buf).unwrap();
+                assert_eq!(&buf[..n], b"bye");
+            })
+        }).collect::<Vec<_>>();
+
+    for client in clients {
+        client.join().unwrap();
+    }
+    server.join().unwrap();
+}
+
+#[test]
+#[cfg(ossl110)]
+fn dtls_retransmit() {
+    let mut ctx = SslContext::builder(SslMethod::dtls()).unwrap();
+    ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_cookie_generate_cb(|_, buf| {
+        buf[..6].copy_from_slice(b"cookie");
+        Ok(6)
+    });
+    ctx.set_cookie_verify_cb(|_, cookie| cookie == b"cookie");
+
+    let listener = DtlsListener::new(ctx.build(), UdpSocket::bind("127.0.0.1:0").unwrap());
+    let server_addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let mut stream = listener.accept().unwrap().handshake().unwrap();
+        let mut buf = [0; 100];
+        let n = stream.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"hello");
+    });
+
+    // forward datagrams between the client and server, dropping the client's first ClientHello
+    let client_side = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let proxy_addr = client_side.local_addr().unwrap();
+    let server_side = UdpSocket::bind("127.0.0.1:0").unwrap();
+    server_side.connect(server_addr).unwrap();
+    let client_addr = Arc::new(Mutex::new(None));
+    {
+        let client_side = client_side.try_clone().unwrap();
+        let server_side = server_side.try_clone().unwrap();
+        let client_addr = client_addr.clone();
+        thread::spawn(move || {
+            let mut buf = [0; 2048];
+            let mut dropped = false;
+            while let Ok((n, addr)) = client_side.recv_from(&mut buf) {
+                *client_addr.lock().unwrap() = Some(addr);
+                if !dropped {
+                    dropped = true;
+                    continue;
+                }
+                server_side.send(&buf[..n]).unwrap();
+            }
+        });
+    }
+    thread::spawn(move || {
+        let mut buf = [0; 2048];
+        while let Ok(n) = server_side.recv(&mut buf) {
+            let addr = client_addr.lock().unwrap().unwrap();
+            client_side.send_to(&buf[..n], addr).unwrap();
+        }
+    });
+
+    let ctx = SslContext::builder(SslMethod::dtls()).unwrap();
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let mut stream = DtlsStream::connect(ssl, socket, proxy_addr).unwrap();
+    stream.send(b"hello").unwrap();
+
+    server.join().unwrap();
+}
+
+#[test]
+fn dtls_timer() {
+    let peer = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let peer_addr = peer.local_addr().unwrap();
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let mut ssl = Ssl::new(&SslContext::builder(SslMethod::dtls()).unwrap().build()).unwrap();
+    ssl.set_mtu(1200).unwrap();
+    assert!(ssl.dtls_timeout().is_none());
+
+    // the peer never responds, so the ClientHello is retransmitted when the timer expires
+    thread::spawn(move || {
+        let _ = DtlsStream::connect(ssl, socket, peer_addr);
+    });
+    let mut buf = [0; 2048];
+    peer.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    let (first, _) = peer.recv_from(&mut buf).unwrap();
+    let (second, _) = peer.recv_from(&mut buf).unwrap();
+    // handshake records, differing only in their sequence numbers
+    assert_eq!(buf[0], 22);
+    assert_eq!(first, second);
+}
+
+#[cfg(feature = "futures-io")]
+mod futures_io {
+    use futures_io::{AsyncRead, AsyncWrite};
+    use std::future::Future;
+    use std::io::{self, Read, Write};
+    use std::net::{Shutdown, TcpListener, TcpStream};
+    use std::pin::Pin;
+    use std::ptr;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{CERT, KEY};
+    use pkey::PKey;
+    use ssl::{AsyncSslStream, SslAcceptor, SslConnector, SslMethod};
+    use x509::X509;
+
+    struct AsyncTcpStream(TcpStream);
+
+    fn cvt<T>(r: io::Result<T>, cx: &mut Context) -> Poll<io::Result<T>> {
+        match r {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            r => Poll::Ready(r),
+        }
+    }
+
+    impl AsyncRead for AsyncTcpStream {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            cvt(self.0.read(buf), cx)
+        }
+    }
+
+    impl AsyncWrite for AsyncTcpStream {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            cvt(self.0.write(buf), cx)
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+            cvt(self.0.flush(), cx)
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+            Poll::Ready(self.0.shutdown(Shutdown::Write))
+        }
+    }
+
+    struct PollFn<F>(F);
+
+    impl<F, T> Future for PollFn<F>
+    where
+        F: FnMut(&mut Context) -> Poll<T> + Unpin,
+    {
+        type Output = T;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<T> {
+            (self.0)(cx)
+        }
+    }
+
+    fn poll_fn<F, T>(f: F) -> PollFn<F>
+    where
+        F: FnMut(&mut Context) -> Poll<T> + Unpin,
+    {
+        PollFn(f)
+    }
+
+    fn block_on<F>(mut f: F) -> F::Output
+    where
+        F: Future + Unpin,
+    {
+        fn clone(p: *const ()) -> RawWaker {
+            RawWaker::new(p, &VTABLE)
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+        let waker = unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) };
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(r) = Pin::new(&mut f).poll(&mut cx) {
+                return r;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn async_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let t = thread::spawn(move || {
+            let key = PKey::private_key_from_pem(KEY).unwrap();
+            let cert = X509::from_pem(CERT).unwrap();
+            let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+            acceptor.set_private_key(&key).unwrap();
+            acceptor.set_certificate(&cert).unwrap();
+            let acceptor = acceptor.build();
+            let stream = listener.accept().unwrap().0;
+            let mut stream = acceptor.accept(stream).unwrap();
+
+            let mut buf = [0; 5];
+            stream.read_exact(&mut buf).unwrap();
+            assert_eq!(b"hello", &buf);
+            stream.write_all(b"world").unwrap();
+
+            // wait for the client's close notify, then for it to close the connection
+            assert_eq!(stream.read(&mut buf).unwrap(), 0);
+            assert_eq!(stream.get_mut().read(&mut buf).unwrap(), 0);
+        });
+
+        let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+        connector.set_ca_file("test/root-ca.pem").unwrap();
+        let ssl = connector
+            .build()
+            .configure()
+            .unwrap()
+            .into_ssl("foobar.com")
+            .unwrap();
+
+        let stream = TcpStream::connect(addr).unwrap();
+        stream.set_nonblocking(true).unwrap();
+        let mut stream = AsyncSslStream::new(ssl, AsyncTcpStream(stream)).unwrap();
+
+        block_on(stream.connect()).unwrap();
+
+        let n = block_on(poll_fn(|cx| Pin::new(&mut stream).poll_write(cx, b"hello"))).unwrap();
+        assert_eq!(n, 5);
+
+        let mut buf = [0; 5];
+        let mut len = 0;
+        while len < buf.len() {
+            let n = block_on(poll_fn(|cx| {
+                Pin::new(&mut stream).poll_read(cx, &mut buf[len..])
+            })).unwrap();
+            assert!(n > 0);
+            len += n;
+        }
+        assert_eq!(b"world", &buf);
+
+        block_on(stream.shutdown()).unwrap();
+        // the close notify has already been sent, so this doesn't wait for the peer's
+        block_on(poll_fn(|cx| Pin::new(&mut stream).poll_close(cx))).unwrap();
+
+        t.join().unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, RwLock};
+
+use error::ErrorStack;
+use rand::rand_bytes;
+
+const TICKET_KEY_NAME_LEN: usize = 16;
+
+/// A key used to encrypt and authenticate session tickets.
+///
+/// Tickets are encrypted with AES-256-CBC and authenticated with HMAC-SHA256, and carry the name
+/// of the key which protects them so that the server can select the right key to decrypt them.
+#[derive(Clone)]
+pub struct SessionTicketKey {
+    name: [u8; TICKET_KEY_NAME_LEN],
+    aes_key: [u8; 32],
+    hmac_key: [u8; 32],
+}
+
+impl SessionTicketKey {
+    /// Creates a key from its name, AES-256 key, and HMAC-SHA256 key.
+    pub fn new(
+        name: [u8; TICKET_KEY_NAME_LEN],
+        aes_key: [u8; 32],
+        hmac_key: [u8; 32],
+    ) -> SessionTicketKey {
+        SessionTicketKey {
+            name,
+            aes_key,
+            hmac_key,
+        }
+    }
+
+    /// Generates a random key.
+    pub fn generate() -> Result<SessionTicketKey, ErrorStack> {
+        let mut key = SessionTicketKey::new([0; TICKET_KEY_NAME_LEN], [0; 32], [0; 32]);
+        rand_bytes(&mut key.name)?;
+        rand_bytes(&mut key.aes_key)?;
+        rand_bytes(&mut key.hmac_key)?;
+        Ok(key)
+    }
+
+    /// Returns the name of the key.
+    pub fn name(&self) -> &[u8; TICKET_KEY_NAME_LEN] {
+        &self.name
+    }
+
+    /// Returns the AES-256 key used to encrypt tickets.
+    pub fn aes_key(&self) -> &[u8; 32] {
+        &self.aes_key
+    }
+
+    /// Returns the HMAC-SHA256 key used to authenticate tickets.
+    pub fn hmac_key(&self) -> &[u8; 32] {
+        &self.hmac_key
+    }
+}
+
+impl fmt::Debug for SessionTicketKey {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("SessionTicketKey")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// The operation a session ticket key callback is asked to perform.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TicketKeyRequest<'a> {
+    /// A new ticket is being issued, and the key which should encrypt it is requested.
+    Encrypt,
+    /// A ticket presented by a client is being decrypted, and the key with the specified name is
+    /// requested.
+    Decrypt(&'a [u8]),
+}
+
+/// The response of a session ticket key callback.
+#[derive(Debug, Clone)]
+pub enum TicketKeyResponse {
+    /// The key should be used to encrypt or decrypt the ticket.
+    Key(SessionTicketKey),
+    /// The key should be used to decrypt the ticket, and a new ticket should be issued to the
+    /// client.
+    ///
+    /// This is typically returned for keys which have been rotated out but are still accepted.
+    /// It is treated like `Key` when encrypting.
+    Renew(SessionTicketKey),
+    /// No key is available.
+    ///
+    /// When encrypting, no ticket is issued. When decrypting, the ticket is rejected and a full
+    /// handshake is performed.
+    None,
+}
+
+struct KeyRing {
+    current: SessionTicketKey,
+    decrypt_only: Vec<SessionTicketKey>,
+}
+
+/// A set of session ticket keys which may be shared between contexts and updated at runtime.
+///
+/// New tickets are encrypted with the current key. Tickets encrypted with older keys which are
+/// still in the ring are accepted, but the client is issued a new ticket encrypted with the
+/// current key.
+///
+/// Servers which share the same keys can resume each other's sessions. The keys can be
+/// installed with `SslContextBuilder::set_session_ticket_keys`, and clones of the ring refer to
+/// the same keys, so a clone can be kept around to rotate them while the context is in use.
+///
+/// # Examples
+///
+/// ```
+/// use openssl::ssl::{SessionTicketKey, SessionTicketKeys, SslContext, SslMethod};
+///
+/// let keys = SessionTicketKeys::new(SessionTicketKey::generate().unwrap());
+///
+/// let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+/// ctx.set_session_ticket_keys(keys.clone()).unwrap();
+/// let ctx = ctx.build();
+///
+/// // later, from a background task
+/// keys.rotate(SessionTicketKey::generate().unwrap());
+/// ```
+#[derive(Clone)]
+pub struct SessionTicketKeys(Arc<RwLock<KeyRing>>);
+
+impl SessionTicketKeys {
+    /// Creates a key ring with the specified current key.
+    pub fn new(current: SessionTicketKey) -> SessionTicketKeys {
+        SessionTicketKeys(Arc::new(RwLock::new(KeyRing {
+            current,
+            decrypt_only: vec![],
+        })))
+    }
+
+    /// Makes `key` the current key.
+    ///
+    /// The previous current key is retained to decrypt existing tickets until it is retired.
+    pub fn rotate(&self, key: SessionTicketKey) {
+        let mut ring = self.0.write().unwrap();
+        let previous = mem::replace(&mut ring.current, key);
+        ring.decrypt_only.retain(|k| k.name != previous.name);
+        ring.decrypt_only.insert(0, previous);
+    }
+
+    /// Replaces all keys in the ring.
+    ///
+    /// `decrypt_only` contains keys which are accepted for existing tickets but no longer used to
+    /// encrypt new ones. This is useful when the keys are distributed from shared storage.
+    pub fn set_keys(&self, current: SessionTicketKey, decrypt_only: Vec<SessionTicketKey>) {
+        let mut ring = self.0.write().unwrap();
+        ring.current = current;
+        ring.decrypt_only = decrypt_only;
+    }
+
+    /// Removes the key with the specified name from the keys used to decrypt existing tickets.
+    ///
+    /// Returns `true` if the key was present. The current key cannot be retired.
+    pub fn retire(&self, name: &[u8]) -> bool {
+        let mut ring = self.0.write().unwrap();
+        let len = ring.decrypt_only.len();
+        ring.decrypt_only.retain(|k| &k.name[..] != name);
+        ring.decrypt_only.len() != len
+    }
+
+    /// Returns the name of the current key.
+    pub fn current_name(&self) -> [u8; TICKET_KEY_NAME_LEN] {
+        self.0.read().unwrap().current.name
+    }
+
+    pub(crate) fn lookup(&self, request: TicketKeyRequest) -> TicketKeyResponse {
+        let ring = self.0.read().unwrap();
+        match request {
+            TicketKeyRequest::Encrypt => TicketKeyResponse::Key(ring.current.clone()),
+            TicketKeyRequest::Decrypt(name) => {
+                if &ring.current.name[..] == name {
+                    return TicketKeyResponse::Key(ring.current.clone());
+                }
+                match ring.decrypt_only.iter().find(|k| &k.name[..] == name) {
+                    Some(key) => TicketKeyResponse::Renew(key.clone()),
+                    None => TicketKeyResponse::None,
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SessionTicketKeys {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let ring = self.0.read().unwrap();
+        fmt.debug_struct("SessionTicketKeys")
+            .field("current", &ring.current)
+            .field("decrypt_only", &ring.decrypt_only)
+            .finish()
+    }
+}
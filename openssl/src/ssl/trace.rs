@@ -0,0 +1,314 @@
+use ffi;
+use libc::c_int;
+use std::fmt;
+
+#[cfg(feature = "log")]
+use ssl::{ErrorCode, SslRef};
+use ssl::{SslAlert, SslVersion};
+
+/// The direction in which a message or alert was sent.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MessageDirection {
+    /// The message was sent to the peer.
+    Sent,
+    /// The message was received from the peer.
+    Received,
+}
+
+/// The content type of a protocol message.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SslContentType(c_int);
+
+impl SslContentType {
+    pub const CHANGE_CIPHER_SPEC: SslContentType = SslContentType(ffi::SSL3_RT_CHANGE_CIPHER_SPEC);
+    pub const ALERT: SslContentType = SslContentType(ffi::SSL3_RT_ALERT);
+    pub const HANDSHAKE: SslContentType = SslContentType(ffi::SSL3_RT_HANDSHAKE);
+    pub const APPLICATION_DATA: SslContentType = SslContentType(ffi::SSL3_RT_APPLICATION_DATA);
+
+    /// A pseudo content type used to report record headers.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(ossl110)]
+    pub const HEADER: SslContentType = SslContentType(ffi::SSL3_RT_HEADER);
+
+    /// A pseudo content type used to report the inner content type of TLSv1.3 records.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub const INNER_CONTENT_TYPE: SslContentType = SslContentType(ffi::SSL3_RT_INNER_CONTENT_TYPE);
+
+    /// Constructs an `SslContentType` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> SslContentType {
+        SslContentType(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        match self.0 {
+            ffi::SSL3_RT_CHANGE_CIPHER_SPEC => Some("ChangeCipherSpec"),
+            ffi::SSL3_RT_ALERT => Some("Alert"),
+            ffi::SSL3_RT_HANDSHAKE => Some("Handshake"),
+            ffi::SSL3_RT_APPLICATION_DATA => Some("ApplicationData"),
+            #[cfg(ossl110)]
+            ffi::SSL3_RT_HEADER => Some("RecordHeader"),
+            #[cfg(ossl111)]
+            ffi::SSL3_RT_INNER_CONTENT_TYPE => Some("InnerContent"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for SslContentType {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => fmt.write_str(name),
+            None => write!(fmt, "SslContentType({})", self.0),
+        }
+    }
+}
+
+/// A protocol message passed to a message callback.
+///
+/// The message is reported without its record header. With OpenSSL 1.1.0 or newer, headers are
+/// reported separately with the `SslContentType::HEADER` content type.
+#[derive(Debug, Copy, Clone)]
+pub struct SslMessage<'a> {
+    pub(crate) direction: MessageDirection,
+    pub(crate) version: c_int,
+    pub(crate) content_type: SslContentType,
+    pub(crate) data: &'a [u8],
+}
+
+impl<'a> SslMessage<'a> {
+    /// Returns the direction in which the message was sent.
+    pub fn direction(&self) -> MessageDirection {
+        self.direction
+    }
+
+    /// Returns the protocol version of the record containing the message, if known.
+    pub fn version(&self) -> Option<SslVersion> {
+        if self.version == 0 {
+            None
+        } else {
+            Some(SslVersion(self.version))
+        }
+    }
+
+    /// Returns the content type of the message.
+    pub fn content_type(&self) -> SslContentType {
+        self.content_type
+    }
+
+    /// Returns the type of a handshake message, such as 1 for `ClientHello`.
+    ///
+    /// This is `None` for messages other than handshake messages.
+    pub fn handshake_type(&self) -> Option<u8> {
+        if self.content_type == SslContentType::HANDSHAKE {
+            self.data.first().cloned()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the raw bytes of the message.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// An event passed to an info callback.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SslInfoEvent {
+    /// A handshake has started.
+    HandshakeStart,
+    /// A handshake has completed.
+    HandshakeDone,
+    /// The handshake has moved to a new state, described by `SslRef::state_string_long`.
+    StateChange,
+    /// A handshake function is returning `ret`.
+    ///
+    /// A value of 0 or less indicates that the handshake has not completed, either because it
+    /// failed or because it would block. The two can be distinguished by passing `ret` to
+    /// [`SSL_get_error`].
+    ///
+    /// [`SSL_get_error`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_get_error.html
+    Exit { ret: c_int },
+    /// An alert was sent or received.
+    Alert {
+        direction: MessageDirection,
+        fatal: bool,
+        alert: SslAlert,
+    },
+}
+
+impl SslInfoEvent {
+    pub(crate) fn from_raw(where_: c_int, ret: c_int) -> Option<SslInfoEvent> {
+        if where_ & ffi::SSL_CB_HANDSHAKE_START != 0 {
+            Some(SslInfoEvent::HandshakeStart)
+        } else if where_ & ffi::SSL_CB_HANDSHAKE_DONE != 0 {
+            Some(SslInfoEvent::HandshakeDone)
+        } else if where_ & ffi::SSL_CB_ALERT != 0 {
+            let direction = if where_ & ffi::SSL_CB_WRITE != 0 {
+                MessageDirection::Sent
+            } else {
+                MessageDirection::Received
+            };
+            Some(SslInfoEvent::Alert {
+                direction,
+                fatal: ret >> 8 == ffi::SSL3_AL_FATAL,
+                alert: SslAlert(ret & 0xff),
+            })
+        } else if where_ & ffi::SSL_CB_LOOP != 0 {
+            Some(SslInfoEvent::StateChange)
+        } else if where_ & ffi::SSL_CB_EXIT != 0 {
+            Some(SslInfoEvent::Exit { ret })
+        } else {
+            None
+        }
+    }
+}
+
+/// Logs a protocol message to the `log` crate, in a form similar to `openssl s_client -msg`.
+///
+/// A summary of each message is logged at the debug level, and its bytes at the trace level. This
+/// function can be passed directly to `SslContextBuilder::set_msg_callback`.
+///
+/// Requires the `log` feature.
+#[cfg(feature = "log")]
+pub fn log_message(ssl: &SslRef, message: SslMessage) {
+    if !log_enabled!(::log::Level::Debug) {
+        return;
+    }
+
+    let arrow = match message.direction {
+        MessageDirection::Sent => ">>>",
+        MessageDirection::Received => "<<<",
+    };
+    let mut summary = format!(
+        "{} {} {} {:?} [length {:04x}]",
+        role(ssl),
+        arrow,
+        version_name(message.version),
+        message.content_type,
+        message.data.len()
+    );
+    if let Some(ty) = message.handshake_type() {
+        summary.push_str(", ");
+        summary.push_str(handshake_type_name(ty));
+    } else if message.content_type == SslContentType::ALERT && message.data.len() == 2 {
+        summary.push_str(", ");
+        summary.push_str(&alert_name(
+            message.data[0] == ffi::SSL3_AL_FATAL as u8,
+            SslAlert(c_int::from(message.data[1])),
+        ));
+    }
+    debug!("{}", summary);
+
+    if log_enabled!(::log::Level::Trace) {
+        for chunk in message.data.chunks(16) {
+            let line = chunk
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(" ");
+            trace!("    {}", line);
+        }
+    }
+}
+
+/// Logs an info callback event to the `log` crate.
+///
+/// Handshake start and completion, alerts, and failures are logged at the debug level, and state
+/// changes and handshake functions returning because they would block at the trace level. This
+/// function can be passed directly to `SslContextBuilder::set_info_callback`.
+///
+/// Requires the `log` feature.
+#[cfg(feature = "log")]
+pub fn log_info(ssl: &SslRef, event: SslInfoEvent) {
+    match event {
+        SslInfoEvent::HandshakeStart => debug!("{} handshake started", role(ssl)),
+        SslInfoEvent::HandshakeDone => debug!("{} handshake done", role(ssl)),
+        SslInfoEvent::StateChange => trace!("{} {}", role(ssl), ssl.state_string_long()),
+        SslInfoEvent::Exit { ret } if ret <= 0 => match ssl.get_error(ret) {
+            ErrorCode::SSL | ErrorCode::SYSCALL | ErrorCode::ZERO_RETURN => {
+                debug!("{} failed in {}", role(ssl), ssl.state_string_long())
+            }
+            _ => trace!("{} blocked in {}", role(ssl), ssl.state_string_long()),
+        },
+        SslInfoEvent::Exit { .. } => {}
+        SslInfoEvent::Alert {
+            direction,
+            fatal,
+            alert,
+        } => {
+            let direction = match direction {
+                MessageDirection::Sent => "sent",
+                MessageDirection::Received => "received",
+            };
+            debug!(
+                "{} {} alert {}",
+                role(ssl),
+                direction,
+                alert_name(fatal, alert)
+            );
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+fn role(ssl: &SslRef) -> &'static str {
+    if ssl.is_server() {
+        "SSL_accept"
+    } else {
+        "SSL_connect"
+    }
+}
+
+#[cfg(feature = "log")]
+fn alert_name(fatal: bool, alert: SslAlert) -> String {
+    let level = if fatal { "fatal" } else { "warning" };
+    format!("{} {}", level, alert.description())
+}
+
+#[cfg(feature = "log")]
+fn version_name(version: c_int) -> &'static str {
+    match version {
+        0x0300 => "SSL 3.0",
+        0x0301 => "TLS 1.0",
+        0x0302 => "TLS 1.1",
+        0x0303 => "TLS 1.2",
+        0x0304 => "TLS 1.3",
+        0xfeff => "DTLS 1.0",
+        0xfefd => "DTLS 1.2",
+        0x0100 => "DTLS 1.0 (bad)",
+        _ => "???",
+    }
+}
+
+#[cfg(feature = "log")]
+fn handshake_type_name(ty: u8) -> &'static str {
+    match ty {
+        0 => "HelloRequest",
+        1 => "ClientHello",
+        2 => "ServerHello",
+        3 => "HelloVerifyRequest",
+        4 => "NewSessionTicket",
+        5 => "EndOfEarlyData",
+        8 => "EncryptedExtensions",
+        11 => "Certificate",
+        12 => "ServerKeyExchange",
+        13 => "CertificateRequest",
+        14 => "ServerHelloDone",
+        15 => "CertificateVerify",
+        16 => "ClientKeyExchange",
+        20 => "Finished",
+        22 => "CertificateStatus",
+        24 => "KeyUpdate",
+        254 => "MessageHash",
+        _ => "Unknown",
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, RwLock};
+
+use ssl::{NameType, SniError, Ssl, SslAlert, SslContext, SslRef};
+
+struct Hosts {
+    exact: HashMap<String, SslContext>,
+    wildcard: HashMap<String, SslContext>,
+    default: Option<SslContext>,
+}
+
+struct VirtualHostState(String);
+
+/// A set of virtual hosts which selects the `SslContext` used for a connection by the server name
+/// sent by the client.
+///
+/// Hosts are identified either by an exact name such as `example.com`, or by a wildcard such as
+/// `*.example.com`, which matches exactly one additional label. Exact names take precedence over
+/// wildcards, and names are compared case-insensitively. Connections which match no host use the
+/// default context, if one is set, and are otherwise rejected with an `unrecognized_name` alert.
+///
+/// The set is installed with `SslAcceptorBuilder::set_virtual_hosts`. Clones refer to the same
+/// hosts, so a clone can be kept to add, replace, or remove hosts while the acceptor is in use.
+/// Changes apply to subsequent handshakes.
+///
+/// Switching to a host's context replaces the connection's certificate, private key, and session
+/// ID context, and callbacks such as ALPN selection are taken from the host's context. Other
+/// settings, such as protocol versions and cipher lists, are those of the acceptor, because they
+/// have already been applied by the time the server name is known.
+///
+/// # Examples
+///
+/// ```no_run
+/// use openssl::ssl::{SslAcceptor, SslContext, SslFiletype, SslMethod, VirtualHosts};
+///
+/// fn host_context(cert: &str, key: &str) -> SslContext {
+///     let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+///     ctx.set_certificate_chain_file(cert).unwrap();
+///     ctx.set_private_key_file(key, SslFiletype::PEM).unwrap();
+///     ctx.build()
+/// }
+///
+/// let hosts = VirtualHosts::new();
+/// hosts.insert("example.com", host_context("example.pem", "example.key")).unwrap();
+/// hosts.insert("*.example.com", host_context("wildcard.pem", "wildcard.key")).unwrap();
+///
+/// let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+/// acceptor.set_virtual_hosts(hosts.clone());
+/// let acceptor = acceptor.build();
+///
+/// // later, after the certificate has been renewed
+/// hosts.insert("example.com", host_context("example.pem", "example.key")).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct VirtualHosts(Arc<RwLock<Hosts>>);
+
+impl VirtualHosts {
+    /// Creates an empty set of virtual hosts.
+    pub fn new() -> VirtualHosts {
+        VirtualHosts(Arc::new(RwLock::new(Hosts {
+            exact: HashMap::new(),
+            wildcard: HashMap::new(),
+            default: None,
+        })))
+    }
+
+    /// Adds or replaces the host with the specified name or wildcard, returning the context it
+    /// previously used.
+    ///
+    /// Returns an error if `name` is empty, or is a wildcard other than a leading `*.` label.
+    pub fn insert(
+        &self,
+        name: &str,
+        ctx: SslContext,
+    ) -> Result<Option<SslContext>, InvalidHostName> {
+        let name = match parse_name(name) {
+            Some(name) => name,
+            None => return Err(InvalidHostName(name.to_string())),
+        };
+        let mut hosts = self.0.write().unwrap();
+        let previous = match name {
+            Name::Exact(name) => hosts.exact.insert(name, ctx),
+            Name::Wildcard(suffix) => hosts.wildcard.insert(suffix, ctx),
+        };
+        Ok(previous)
+    }
+
+    /// Removes the host with the specified name or wildcard, returning the context it used.
+    ///
+    /// Returns `None` if no such host exists, including if `name` is not a valid name.
+    pub fn remove(&self, name: &str) -> Option<SslContext> {
+        let name = match parse_name(name) {
+            Some(name) => name,
+            None => return None,
+        };
+        let mut hosts = self.0.write().unwrap();
+        match name {
+            Name::Exact(name) => hosts.exact.remove(&name),
+            Name::Wildcard(suffix) => hosts.wildcard.remove(&suffix),
+        }
+    }
+
+    /// Sets the context used for connections which match no host, returning the previous one.
+    ///
+    /// This includes connections from clients which do not send a server name.
+    pub fn set_default(&self, ctx: Option<SslContext>) -> Option<SslContext> {
+        let mut hosts = self.0.write().unwrap();
+        mem::replace(&mut hosts.default, ctx)
+    }
+
+    /// Returns the name of the host matching `server_name` and its context.
+    ///
+    /// The name is `None` if the default context was selected.
+    pub fn lookup(&self, server_name: Option<&str>) -> Option<(Option<String>, SslContext)> {
+        let hosts = self.0.read().unwrap();
+
+        if let Some(server_name) = server_name {
+            let server_name = normalize(server_name);
+            if let Some(ctx) = hosts.exact.get(&server_name) {
+                return Some((Some(server_name.clone()), ctx.clone()));
+            }
+
+            if let Some(dot) = server_name.find('.') {
+                let suffix = &server_name[dot + 1..];
+                if dot > 0 {
+                    if let Some(ctx) = hosts.wildcard.get(suffix) {
+                        return Some((Some(format!("*.{}", suffix)), ctx.clone()));
+                    }
+                }
+            }
+        }
+
+        hosts.default.as_ref().map(|ctx| (None, ctx.clone()))
+    }
+
+    pub(crate) fn select(&self, ssl: &mut SslRef, alert: &mut SslAlert) -> Result<(), SniError> {
+        let (name, ctx) = {
+            let server_name = ssl.servername(NameType::HOST_NAME);
+            match self.lookup(server_name) {
+                Some(host) => host,
+                None => {
+                    *alert = SslAlert::UNRECOGNIZED_NAME;
+                    return Err(SniError::ALERT_FATAL);
+                }
+            }
+        };
+
+        // the connection holds its own reference to the context, so it is unaffected if the host
+        // is replaced or removed during the handshake
+        if ssl.set_ssl_context(&ctx).is_err() {
+            return Err(SniError::ALERT_FATAL);
+        }
+        if let Some(name) = name {
+            ssl.set_ex_data(
+                Ssl::cached_ex_index::<VirtualHostState>(),
+                VirtualHostState(name),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for VirtualHosts {
+    fn default() -> VirtualHosts {
+        VirtualHosts::new()
+    }
+}
+
+impl fmt::Debug for VirtualHosts {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let hosts = self.0.read().unwrap();
+        let mut names = hosts
+            .exact
+            .keys()
+            .cloned()
+            .chain(hosts.wildcard.keys().map(|s| format!("*.{}", s)))
+            .collect::<Vec<_>>();
+        names.sort();
+        fmt.debug_struct("VirtualHosts")
+            .field("hosts", &names)
+            .field("default", &hosts.default.is_some())
+            .finish()
+    }
+}
+
+/// An error returned by `VirtualHosts::insert` for an invalid host name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidHostName(String);
+
+impl fmt::Display for InvalidHostName {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "invalid virtual host name `{}`", self.0)
+    }
+}
+
+impl Error for InvalidHostName {
+    fn description(&self) -> &str {
+        "an invalid virtual host name"
+    }
+}
+
+enum Name {
+    Exact(String),
+    Wildcard(String),
+}
+
+fn parse_name(name: &str) -> Option<Name> {
+    let name = normalize(name);
+    let wildcard = name.starts_with("*.");
+    let rest = if wildcard { &name[2..] } else { &name[..] };
+    if rest.is_empty() || rest.contains('*') {
+        return None;
+    }
+
+    if wildcard {
+        Some(Name::Wildcard(rest.to_string()))
+    } else {
+        Some(Name::Exact(rest.to_string()))
+    }
+}
+
+fn normalize(name: &str) -> String {
+    let name = if name.ends_with('.') {
+        &name[..name.len() - 1]
+    } else {
+        name
+    };
+    name.to_lowercase()
+}
+
+impl SslRef {
+    /// Returns the name of the virtual host selected for the connection by `VirtualHosts`.
+    ///
+    /// This is the exact name or wildcard the host was registered with, and is `None` if the
+    /// default context was used or no virtual hosts are configured.
+    pub fn virtual_host(&self) -> Option<&str> {
+        self.ex_data(Ssl::cached_ex_index::<VirtualHostState>())
+            .map(|state| &*state.0)
+    }
+}
@@ -20,6 +20,8 @@ use std::ptr;
 use std::slice;
 use std::str;
 
+#[cfg(ossl110)]
+use asn1::Asn1Object;
 use asn1::{Asn1BitStringRef, Asn1IntegerRef, Asn1ObjectRef, Asn1StringRef, Asn1TimeRef};
 use bio::MemBioSlice;
 use conf::ConfRef;
@@ -37,6 +39,7 @@ use {cvt, cvt_n, cvt_p};
 pub mod verify;
 
 pub mod extension;
+pub mod spki;
 pub mod store;
 
 #[cfg(test)]
@@ -231,6 +234,21 @@ impl X509Builder {
         }
     }
 
+    /// Creates a new builder initialized with a copy of an existing certificate.
+    ///
+    /// All fields and extensions of `cert` are retained, so only the parts which should change
+    /// need to be set before the certificate is signed again.
+    ///
+    /// This corresponds to [`X509_dup`].
+    ///
+    /// [`X509_dup`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_dup.html
+    pub fn from_certificate(cert: &X509Ref) -> Result<X509Builder, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::X509_dup(cert.as_ptr())).map(|p| X509Builder(X509(p)))
+        }
+    }
+
     /// Sets the notAfter constraint on the certificate.
     pub fn set_not_after(&mut self, not_after: &Asn1TimeRef) -> Result<(), ErrorStack> {
         unsafe { cvt(X509_set1_notAfter(self.0.as_ptr(), not_after.as_ptr())).map(|_| ()) }
@@ -347,6 +365,123 @@ impl X509Builder {
         }
     }
 
+    /// Replaces the extension with the same type as `extension`, or appends it if the
+    /// certificate has no such extension.
+    ///
+    /// The replacement takes the position of the first existing extension of the same type, and
+    /// any further extensions of that type are removed.
+    pub fn replace_extension(&mut self, extension: X509Extension) -> Result<(), ErrorStack> {
+        unsafe {
+            let obj = ffi::X509_EXTENSION_get_object(extension.as_ptr());
+            let loc = ffi::X509_get_ext_by_OBJ(self.0.as_ptr(), obj, -1);
+            if loc >= 0 {
+                loop {
+                    let next = ffi::X509_get_ext_by_OBJ(self.0.as_ptr(), obj, loc);
+                    if next < 0 {
+                        break;
+                    }
+                    ffi::X509_EXTENSION_free(ffi::X509_delete_ext(self.0.as_ptr(), next));
+                }
+                ffi::X509_EXTENSION_free(ffi::X509_delete_ext(self.0.as_ptr(), loc));
+            }
+
+            // X509_add_ext adds a copy of the extension
+            cvt(ffi::X509_add_ext(self.0.as_ptr(), extension.as_ptr(), loc)).map(|_| ())
+        }
+    }
+
+    /// Removes all extensions of the specified type from the certificate.
+    ///
+    /// Returns `true` if any extensions were removed.
+    pub fn remove_extension(&mut self, nid: Nid) -> bool {
+        unsafe {
+            let mut removed = false;
+            loop {
+                let loc = ffi::X509_get_ext_by_NID(self.0.as_ptr(), nid.as_raw(), -1);
+                if loc < 0 {
+                    return removed;
+                }
+                ffi::X509_EXTENSION_free(ffi::X509_delete_ext(self.0.as_ptr(), loc));
+                removed = true;
+            }
+        }
+    }
+
+    /// Sets the alias ("friendly name") of the certificate.
+    ///
+    /// The alias is part of the certificate's auxiliary trust information and is not covered by
+    /// its signature.
+    ///
+    /// This corresponds to [`X509_alias_set1`].
+    ///
+    /// [`X509_alias_set1`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_alias_set1.html
+    pub fn set_alias(&mut self, alias: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(alias.len() <= c_int::max_value() as usize);
+            cvt(ffi::X509_alias_set1(
+                self.0.as_ptr(),
+                alias.as_ptr(),
+                alias.len() as c_int,
+            )).map(|_| ())
+        }
+    }
+
+    /// Sets the key identifier in the certificate's auxiliary trust information.
+    ///
+    /// This corresponds to [`X509_keyid_set1`].
+    ///
+    /// [`X509_keyid_set1`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_keyid_set1.html
+    pub fn set_keyid(&mut self, keyid: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(keyid.len() <= c_int::max_value() as usize);
+            cvt(ffi::X509_keyid_set1(
+                self.0.as_ptr(),
+                keyid.as_ptr(),
+                keyid.len() as c_int,
+            )).map(|_| ())
+        }
+    }
+
+    /// Marks the certificate as trusted for the purpose identified by `obj`.
+    ///
+    /// The object is typically an extended key usage such as `Nid::CLIENT_AUTH`. Once any trusted
+    /// purpose is set, a trust anchor is rejected for all purposes not in its trust list. Use
+    /// `from_certificate` to change the trust settings of an existing certificate.
+    ///
+    /// This corresponds to [`X509_add1_trust_object`].
+    ///
+    /// [`X509_add1_trust_object`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_add1_trust_object.html
+    pub fn add_trust_object(&mut self, obj: &Asn1ObjectRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_add1_trust_object(self.0.as_ptr(), obj.as_ptr())).map(|_| ()) }
+    }
+
+    /// Marks the certificate as rejected for the purpose identified by `obj`.
+    ///
+    /// This corresponds to [`X509_add1_reject_object`].
+    ///
+    /// [`X509_add1_reject_object`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_add1_reject_object.html
+    pub fn add_reject_object(&mut self, obj: &Asn1ObjectRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_add1_reject_object(self.0.as_ptr(), obj.as_ptr())).map(|_| ()) }
+    }
+
+    /// Removes all trusted purposes from the certificate.
+    ///
+    /// This corresponds to [`X509_trust_clear`].
+    ///
+    /// [`X509_trust_clear`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_trust_clear.html
+    pub fn clear_trust(&mut self) {
+        unsafe { ffi::X509_trust_clear(self.0.as_ptr()) }
+    }
+
+    /// Removes all rejected purposes from the certificate.
+    ///
+    /// This corresponds to [`X509_reject_clear`].
+    ///
+    /// [`X509_reject_clear`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_reject_clear.html
+    pub fn clear_reject(&mut self) {
+        unsafe { ffi::X509_reject_clear(self.0.as_ptr()) }
+    }
+
     /// Signs the certificate with a private key.
     pub fn sign<T>(&mut self, key: &PKeyRef<T>, hash: MessageDigest) -> Result<(), ErrorStack>
     where
@@ -541,6 +676,88 @@ impl X509Ref {
         }
     }
 
+    /// Returns the alias ("friendly name") attached to the certificate, if any.
+    ///
+    /// This corresponds to [`X509_alias_get0`].
+    ///
+    /// [`X509_alias_get0`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_alias_get0.html
+    pub fn alias(&self) -> Option<&[u8]> {
+        unsafe {
+            let mut len = 0;
+            let ptr = ffi::X509_alias_get0(self.as_ptr(), &mut len);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(slice::from_raw_parts(ptr as *const u8, len as usize))
+            }
+        }
+    }
+
+    /// Returns the key identifier attached to the certificate's auxiliary trust information, if
+    /// any.
+    ///
+    /// This corresponds to [`X509_keyid_get0`].
+    ///
+    /// [`X509_keyid_get0`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_keyid_get0.html
+    pub fn keyid(&self) -> Option<&[u8]> {
+        unsafe {
+            let mut len = 0;
+            let ptr = ffi::X509_keyid_get0(self.as_ptr(), &mut len);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(slice::from_raw_parts(ptr as *const u8, len as usize))
+            }
+        }
+    }
+
+    /// Returns the purposes the certificate is explicitly trusted for, if any.
+    ///
+    /// This corresponds to [`X509_get0_trust_objects`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`X509_get0_trust_objects`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_get0_trust_objects.html
+    #[cfg(ossl110)]
+    pub fn trust_objects(&self) -> Option<&StackRef<Asn1Object>> {
+        unsafe {
+            let stack = ffi::X509_get0_trust_objects(self.as_ptr());
+            if stack.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr(stack))
+            }
+        }
+    }
+
+    /// Returns the purposes the certificate is explicitly rejected for, if any.
+    ///
+    /// This corresponds to [`X509_get0_reject_objects`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`X509_get0_reject_objects`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_get0_reject_objects.html
+    #[cfg(ossl110)]
+    pub fn reject_objects(&self) -> Option<&StackRef<Asn1Object>> {
+        unsafe {
+            let stack = ffi::X509_get0_reject_objects(self.as_ptr());
+            if stack.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr(stack))
+            }
+        }
+    }
+
+    /// Checks whether the certificate is trusted for the specified trust setting.
+    ///
+    /// This corresponds to [`X509_check_trust`].
+    ///
+    /// [`X509_check_trust`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_check_trust.html
+    pub fn check_trust(&self, trust: X509Trust) -> X509TrustResult {
+        unsafe { X509TrustResult(ffi::X509_check_trust(self.as_ptr(), trust.as_raw(), 0)) }
+    }
+
     to_pem! {
         /// Serializes the certificate into a PEM-encoded X509 structure.
         ///
@@ -553,6 +770,19 @@ impl X509Ref {
         ffi::PEM_write_bio_X509
     }
 
+    to_pem! {
+        /// Serializes the certificate along with its auxiliary trust information into a
+        /// PEM-encoded structure.
+        ///
+        /// The output will have a header of `-----BEGIN TRUSTED CERTIFICATE-----`.
+        ///
+        /// This corresponds to [`PEM_write_bio_X509_AUX`].
+        ///
+        /// [`PEM_write_bio_X509_AUX`]: https://www.openssl.org/docs/man1.0.2/crypto/PEM_write_bio_X509_AUX.html
+        to_trusted_pem,
+        ffi::PEM_write_bio_X509_AUX
+    }
+
     to_der! {
         /// Serializes the certificate into a DER-encoded X509 structure.
         ///
@@ -594,6 +824,20 @@ impl X509 {
         ffi::PEM_read_bio_X509
     }
 
+    from_pem! {
+        /// Deserializes a PEM-encoded X509 structure along with its auxiliary trust information.
+        ///
+        /// The input should have a header of `-----BEGIN TRUSTED CERTIFICATE-----`, though plain
+        /// certificates with a header of `-----BEGIN CERTIFICATE-----` are also accepted.
+        ///
+        /// This corresponds to [`PEM_read_bio_X509_AUX`].
+        ///
+        /// [`PEM_read_bio_X509_AUX`]: https://www.openssl.org/docs/man1.0.2/crypto/PEM_read_bio_X509_AUX.html
+        from_trusted_pem,
+        X509,
+        ffi::PEM_read_bio_X509_AUX
+    }
+
     from_der! {
         /// Deserializes a DER-encoded X509 structure.
         ///
@@ -998,6 +1242,48 @@ impl X509ReqBuilder {
         }
     }
 
+    /// Adds an attribute with a string value to the request.
+    ///
+    /// This can be used to add PKCS#9 attributes such as `Nid::PKCS9_CHALLENGEPASSWORD` and
+    /// `Nid::PKCS9_UNSTRUCTUREDNAME`.
+    ///
+    /// This corresponds to [`X509_REQ_add1_attr_by_NID`].
+    ///
+    /// [`X509_REQ_add1_attr_by_NID`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REQ_add1_attr_by_NID.html
+    pub fn add_attribute_by_nid(&mut self, nid: Nid, value: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(value.len() <= c_int::max_value() as usize);
+            cvt(ffi::X509_REQ_add1_attr_by_NID(
+                self.0.as_ptr(),
+                nid.as_raw(),
+                ffi::MBSTRING_UTF8,
+                value.as_ptr(),
+                value.len() as c_int,
+            )).map(|_| ())
+        }
+    }
+
+    /// Adds an attribute with a string value to the request.
+    ///
+    /// The attribute type may be specified by its short name, long name, or dotted OID.
+    ///
+    /// This corresponds to [`X509_REQ_add1_attr_by_txt`].
+    ///
+    /// [`X509_REQ_add1_attr_by_txt`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REQ_add1_attr_by_txt.html
+    pub fn add_attribute_by_text(&mut self, name: &str, value: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            let name = CString::new(name).unwrap();
+            assert!(value.len() <= c_int::max_value() as usize);
+            cvt(ffi::X509_REQ_add1_attr_by_txt(
+                self.0.as_ptr(),
+                name.as_ptr(),
+                ffi::MBSTRING_UTF8,
+                value.as_ptr(),
+                value.len() as c_int,
+            )).map(|_| ())
+        }
+    }
+
     /// Sign the request using a private key.
     ///
     /// This corresponds to [`X509_REQ_sign`].
@@ -1129,6 +1415,182 @@ impl X509ReqRef {
             Ok(Stack::from_ptr(extensions))
         }
     }
+
+    /// Returns an iterator over all attributes of the certificate request.
+    pub fn attributes<'a>(&'a self) -> X509ReqAttributes<'a> {
+        X509ReqAttributes {
+            req: self,
+            nid: None,
+            loc: -1,
+        }
+    }
+
+    /// Returns the attributes of the certificate request with the specified type.
+    ///
+    /// This corresponds to [`X509_REQ_get_attr_by_NID`].
+    ///
+    /// [`X509_REQ_get_attr_by_NID`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REQ_get_attr_by_NID.html
+    pub fn attributes_by_nid<'a>(&'a self, nid: Nid) -> X509ReqAttributes<'a> {
+        X509ReqAttributes {
+            req: self,
+            nid: Some(nid),
+            loc: -1,
+        }
+    }
+
+    /// Returns the signature of the certificate request.
+    ///
+    /// This corresponds to [`X509_REQ_get0_signature`].
+    ///
+    /// [`X509_REQ_get0_signature`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REQ_get0_signature.html
+    pub fn signature(&self) -> &Asn1BitStringRef {
+        unsafe {
+            let mut signature = ptr::null();
+            X509_REQ_get0_signature(self.as_ptr(), &mut signature, ptr::null_mut());
+            assert!(!signature.is_null());
+            Asn1BitStringRef::from_ptr(signature as *mut _)
+        }
+    }
+
+    /// Returns the signature algorithm of the certificate request.
+    ///
+    /// This corresponds to [`X509_REQ_get0_signature`].
+    ///
+    /// [`X509_REQ_get0_signature`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REQ_get0_signature.html
+    pub fn signature_algorithm(&self) -> &X509AlgorithmRef {
+        unsafe {
+            let mut algor = ptr::null();
+            X509_REQ_get0_signature(self.as_ptr(), ptr::null_mut(), &mut algor);
+            assert!(!algor.is_null());
+            X509AlgorithmRef::from_ptr(algor as *mut _)
+        }
+    }
+
+    /// Checks that the certificate request is signed by the private key corresponding to `key`.
+    ///
+    /// To check the request's self-signature, pass the result of `public_key`.
+    ///
+    /// This corresponds to [`X509_REQ_verify`].
+    ///
+    /// [`X509_REQ_verify`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REQ_verify.html
+    pub fn verify<T>(&self, key: &PKeyRef<T>) -> Result<bool, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe {
+            match ffi::X509_REQ_verify(self.as_ptr(), key.as_ptr()) {
+                1 => Ok(true),
+                0 => {
+                    ErrorStack::get(); // discard error stack
+                    Ok(false)
+                }
+                _ => Err(ErrorStack::get()),
+            }
+        }
+    }
+}
+
+/// An iterator over the attributes of an `X509Req`.
+pub struct X509ReqAttributes<'a> {
+    req: &'a X509ReqRef,
+    nid: Option<Nid>,
+    loc: c_int,
+}
+
+impl<'a> Iterator for X509ReqAttributes<'a> {
+    type Item = &'a X509AttributeRef;
+
+    fn next(&mut self) -> Option<&'a X509AttributeRef> {
+        unsafe {
+            match self.nid {
+                Some(nid) => {
+                    self.loc =
+                        ffi::X509_REQ_get_attr_by_NID(self.req.as_ptr(), nid.as_raw(), self.loc);
+                    if self.loc < 0 {
+                        return None;
+                    }
+                }
+                None => {
+                    self.loc += 1;
+                    if self.loc >= ffi::X509_REQ_get_attr_count(self.req.as_ptr()) {
+                        return None;
+                    }
+                }
+            }
+
+            let attr = ffi::X509_REQ_get_attr(self.req.as_ptr(), self.loc);
+            assert!(!attr.is_null());
+
+            Some(X509AttributeRef::from_ptr(attr))
+        }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::X509_ATTRIBUTE;
+    fn drop = ffi::X509_ATTRIBUTE_free;
+
+    /// An attribute of a certificate request, such as a PKCS#9 challenge password.
+    pub struct X509Attribute;
+    /// Reference to `X509Attribute`.
+    pub struct X509AttributeRef;
+}
+
+impl X509AttributeRef {
+    /// Returns the type of the attribute.
+    ///
+    /// This corresponds to [`X509_ATTRIBUTE_get0_object`].
+    ///
+    /// [`X509_ATTRIBUTE_get0_object`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_ATTRIBUTE_get0_object.html
+    pub fn object(&self) -> &Asn1ObjectRef {
+        unsafe {
+            let object = ffi::X509_ATTRIBUTE_get0_object(self.as_ptr());
+            assert!(!object.is_null());
+            Asn1ObjectRef::from_ptr(object)
+        }
+    }
+
+    /// Returns the number of values in the attribute.
+    ///
+    /// This corresponds to [`X509_ATTRIBUTE_count`].
+    ///
+    /// [`X509_ATTRIBUTE_count`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_ATTRIBUTE_count.html
+    pub fn value_count(&self) -> usize {
+        unsafe { ffi::X509_ATTRIBUTE_count(self.as_ptr()) as usize }
+    }
+
+    /// Returns the value at the specified index, if it exists and has a string type.
+    ///
+    /// This corresponds to [`X509_ATTRIBUTE_get0_data`].
+    ///
+    /// [`X509_ATTRIBUTE_get0_data`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_ATTRIBUTE_get0_data.html
+    pub fn value(&self, idx: usize) -> Option<&Asn1StringRef> {
+        unsafe {
+            if idx >= self.value_count() {
+                return None;
+            }
+
+            let ty = ffi::X509_ATTRIBUTE_get0_type(self.as_ptr(), idx as c_int);
+            if ty.is_null() {
+                return None;
+            }
+            let ty = ffi::ASN1_TYPE_get(ty);
+            match ty {
+                ffi::V_ASN1_UNDEF | ffi::V_ASN1_BOOLEAN | ffi::V_ASN1_NULL | ffi::V_ASN1_OBJECT => {
+                    return None
+                }
+                _ => {}
+            }
+
+            let data =
+                ffi::X509_ATTRIBUTE_get0_data(self.as_ptr(), idx as c_int, ty, ptr::null_mut());
+            if data.is_null() {
+                None
+            } else {
+                Some(Asn1StringRef::from_ptr(data as *mut _))
+            }
+        }
+    }
 }
 
 /// The result of peer certificate verification.
@@ -1191,6 +1653,97 @@ impl X509VerifyResult {
     /// Application verification failure.
     pub const APPLICATION_VERIFICATION: X509VerifyResult =
         X509VerifyResult(ffi::X509_V_ERR_APPLICATION_VERIFICATION);
+    /// The root CA is not marked as trusted for the specified purpose.
+    pub const CERT_UNTRUSTED: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_CERT_UNTRUSTED);
+    /// The root CA is marked to reject the specified purpose.
+    pub const CERT_REJECTED: X509VerifyResult = X509VerifyResult(ffi::X509_V_ERR_CERT_REJECTED);
+}
+
+/// A trust setting used when checking a certificate's auxiliary trust information.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct X509Trust(c_int);
+
+impl X509Trust {
+    /// Trust self-signed certificates unless they carry explicit trust information.
+    pub const COMPAT: X509Trust = X509Trust(ffi::X509_TRUST_COMPAT);
+    /// Trust for TLS client authentication.
+    pub const SSL_CLIENT: X509Trust = X509Trust(ffi::X509_TRUST_SSL_CLIENT);
+    /// Trust for TLS server authentication.
+    pub const SSL_SERVER: X509Trust = X509Trust(ffi::X509_TRUST_SSL_SERVER);
+    /// Trust for S/MIME email protection.
+    pub const EMAIL: X509Trust = X509Trust(ffi::X509_TRUST_EMAIL);
+    /// Trust for code signing.
+    pub const OBJECT_SIGN: X509Trust = X509Trust(ffi::X509_TRUST_OBJECT_SIGN);
+    /// Trust for signing OCSP responses.
+    pub const OCSP_SIGN: X509Trust = X509Trust(ffi::X509_TRUST_OCSP_SIGN);
+    /// Trust for signing OCSP requests.
+    pub const OCSP_REQUEST: X509Trust = X509Trust(ffi::X509_TRUST_OCSP_REQUEST);
+    /// Trust for timestamping.
+    pub const TSA: X509Trust = X509Trust(ffi::X509_TRUST_TSA);
+
+    /// Constructs an `X509Trust` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> X509Trust {
+        X509Trust(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// The result of checking a certificate's trust settings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct X509TrustResult(c_int);
+
+impl X509TrustResult {
+    /// The certificate is explicitly trusted.
+    pub const TRUSTED: X509TrustResult = X509TrustResult(ffi::X509_TRUST_TRUSTED);
+    /// The certificate is explicitly rejected.
+    pub const REJECTED: X509TrustResult = X509TrustResult(ffi::X509_TRUST_REJECTED);
+    /// The certificate has no trust information applicable to the setting.
+    pub const UNTRUSTED: X509TrustResult = X509TrustResult(ffi::X509_TRUST_UNTRUSTED);
+
+    /// Returns the raw OpenSSL value represented by this type.
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// The purpose a certificate chain is verified for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct X509Purpose(c_int);
+
+impl X509Purpose {
+    /// TLS client authentication.
+    pub const SSL_CLIENT: X509Purpose = X509Purpose(ffi::X509_PURPOSE_SSL_CLIENT);
+    /// TLS server authentication.
+    pub const SSL_SERVER: X509Purpose = X509Purpose(ffi::X509_PURPOSE_SSL_SERVER);
+    /// Netscape TLS server authentication.
+    pub const NS_SSL_SERVER: X509Purpose = X509Purpose(ffi::X509_PURPOSE_NS_SSL_SERVER);
+    /// S/MIME signing.
+    pub const SMIME_SIGN: X509Purpose = X509Purpose(ffi::X509_PURPOSE_SMIME_SIGN);
+    /// S/MIME encryption.
+    pub const SMIME_ENCRYPT: X509Purpose = X509Purpose(ffi::X509_PURPOSE_SMIME_ENCRYPT);
+    /// CRL signing.
+    pub const CRL_SIGN: X509Purpose = X509Purpose(ffi::X509_PURPOSE_CRL_SIGN);
+    /// Any purpose.
+    pub const ANY: X509Purpose = X509Purpose(ffi::X509_PURPOSE_ANY);
+    /// OCSP response signing.
+    pub const OCSP_HELPER: X509Purpose = X509Purpose(ffi::X509_PURPOSE_OCSP_HELPER);
+    /// Timestamp signing.
+    pub const TIMESTAMP_SIGN: X509Purpose = X509Purpose(ffi::X509_PURPOSE_TIMESTAMP_SIGN);
+
+    /// Constructs an `X509Purpose` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> X509Purpose {
+        X509Purpose(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
 }
 
 foreign_type_and_impl_send_sync! {
@@ -1323,6 +1876,7 @@ cfg_if! {
         use ffi::{
             X509_ALGOR_get0, ASN1_STRING_get0_data, X509_STORE_CTX_get0_chain, X509_set1_notAfter,
             X509_set1_notBefore, X509_REQ_get_version, X509_REQ_get_subject_name,
+            X509_REQ_get0_signature,
         };
     } else {
         use ffi::{
@@ -1342,6 +1896,20 @@ cfg_if! {
             (*(*x).req_info).subject
         }
 
+        #[allow(bad_style)]
+        unsafe fn X509_REQ_get0_signature(
+            x: *mut ffi::X509_REQ,
+            psig: *mut *const ffi::ASN1_BIT_STRING,
+            palg: *mut *const ffi::X509_ALGOR,
+        ) {
+            if !psig.is_null() {
+                *psig = (*x).signature;
+            }
+            if !palg.is_null() {
+                *palg = (*x).sig_alg;
+            }
+        }
+
         #[allow(bad_style)]
         unsafe fn X509_ALGOR_get0(
             paobj: *mut *const ffi::ASN1_OBJECT,
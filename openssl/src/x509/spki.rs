@@ -0,0 +1,225 @@
+//! Netscape signed public key and challenge (SPKAC) structures.
+//!
+//! SPKACs are produced by the legacy HTML `<keygen>` element and by a number of enrollment
+//! protocols. They bind a public key to a challenge string, and are signed with the
+//! corresponding private key.
+//!
+//! # Example
+//!
+//! ```rust
+//! extern crate openssl;
+//!
+//! use openssl::hash::MessageDigest;
+//! use openssl::pkey::PKey;
+//! use openssl::rsa::Rsa;
+//! use openssl::x509::spki::{Spki, SpkiBuilder};
+//!
+//! fn main() {
+//!     let rsa = Rsa::generate(2048).unwrap();
+//!     let pkey = PKey::from_rsa(rsa).unwrap();
+//!
+//!     let mut builder = SpkiBuilder::new().unwrap();
+//!     builder.set_pubkey(&pkey).unwrap();
+//!     builder.set_challenge("challenge").unwrap();
+//!     builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+//!     let spki = builder.build();
+//!
+//!     let encoded = spki.to_base64().unwrap();
+//!     let spki = Spki::from_base64(&encoded).unwrap();
+//!     assert!(spki.verify(&spki.public_key().unwrap()).unwrap());
+//! }
+//! ```
+
+use ffi;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::c_int;
+use std::ptr;
+
+use asn1::Asn1StringRef;
+use error::ErrorStack;
+use hash::MessageDigest;
+use pkey::{HasPrivate, HasPublic, PKey, PKeyRef, Public};
+use string::OpensslString;
+use {cvt, cvt_p};
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::NETSCAPE_SPKI;
+    fn drop = ffi::NETSCAPE_SPKI_free;
+
+    /// A Netscape signed public key and challenge.
+    pub struct Spki;
+    /// Reference to `Spki`.
+    pub struct SpkiRef;
+}
+
+impl Spki {
+    /// Returns a new builder.
+    pub fn builder() -> Result<SpkiBuilder, ErrorStack> {
+        SpkiBuilder::new()
+    }
+
+    /// Deserializes a base64-encoded SPKAC, as submitted by a `<keygen>` form field.
+    ///
+    /// This corresponds to [`NETSCAPE_SPKI_b64_decode`].
+    ///
+    /// [`NETSCAPE_SPKI_b64_decode`]: https://www.openssl.org/docs/man1.1.0/crypto/NETSCAPE_SPKI_b64_decode.html
+    pub fn from_base64(s: &str) -> Result<Spki, ErrorStack> {
+        unsafe {
+            ffi::init();
+            assert!(s.len() <= c_int::max_value() as usize);
+            // OpenSSL falls back to strlen for empty input, so make sure it's terminated
+            let mut buf = Vec::with_capacity(s.len() + 1);
+            buf.extend_from_slice(s.as_bytes());
+            buf.push(0);
+            cvt_p(ffi::NETSCAPE_SPKI_b64_decode(
+                buf.as_ptr() as *const _,
+                s.len() as c_int,
+            )).map(Spki)
+        }
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded SPKAC.
+        ///
+        /// This corresponds to [`d2i_NETSCAPE_SPKI`].
+        ///
+        /// [`d2i_NETSCAPE_SPKI`]: https://www.openssl.org/docs/man1.1.0/crypto/d2i_NETSCAPE_SPKI.html
+        from_der,
+        Spki,
+        ffi::d2i_NETSCAPE_SPKI
+    }
+}
+
+impl SpkiRef {
+    /// Returns the public key contained in the SPKAC.
+    ///
+    /// This corresponds to [`NETSCAPE_SPKI_get_pubkey`].
+    ///
+    /// [`NETSCAPE_SPKI_get_pubkey`]: https://www.openssl.org/docs/man1.1.0/crypto/NETSCAPE_SPKI_get_pubkey.html
+    pub fn public_key(&self) -> Result<PKey<Public>, ErrorStack> {
+        unsafe {
+            let pkey = cvt_p(ffi::NETSCAPE_SPKI_get_pubkey(self.as_ptr()))?;
+            Ok(PKey::from_ptr(pkey))
+        }
+    }
+
+    /// Returns the challenge string contained in the SPKAC.
+    pub fn challenge(&self) -> &Asn1StringRef {
+        unsafe {
+            let challenge = (*(*self.as_ptr()).spkac).challenge;
+            assert!(!challenge.is_null());
+            Asn1StringRef::from_ptr(challenge)
+        }
+    }
+
+    /// Checks that the SPKAC is signed by the private key corresponding to `key`.
+    ///
+    /// Note that this does not check that `key` is the key contained in the SPKAC. To check a
+    /// self-signed SPKAC, pass the result of `public_key`.
+    ///
+    /// This corresponds to [`NETSCAPE_SPKI_verify`].
+    ///
+    /// [`NETSCAPE_SPKI_verify`]: https://www.openssl.org/docs/man1.1.0/crypto/NETSCAPE_SPKI_verify.html
+    pub fn verify<T>(&self, key: &PKeyRef<T>) -> Result<bool, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe {
+            match ffi::NETSCAPE_SPKI_verify(self.as_ptr(), key.as_ptr()) {
+                1 => Ok(true),
+                0 => {
+                    ErrorStack::get(); // discard error stack
+                    Ok(false)
+                }
+                _ => Err(ErrorStack::get()),
+            }
+        }
+    }
+
+    /// Serializes the SPKAC into the base64 form used by `<keygen>` form fields.
+    ///
+    /// This corresponds to [`NETSCAPE_SPKI_b64_encode`].
+    ///
+    /// [`NETSCAPE_SPKI_b64_encode`]: https://www.openssl.org/docs/man1.1.0/crypto/NETSCAPE_SPKI_b64_encode.html
+    pub fn to_base64(&self) -> Result<OpensslString, ErrorStack> {
+        unsafe {
+            let buf = cvt_p(ffi::NETSCAPE_SPKI_b64_encode(self.as_ptr()))?;
+            Ok(OpensslString::from_ptr(buf))
+        }
+    }
+
+    to_der! {
+        /// Serializes the SPKAC into a DER-encoded structure.
+        ///
+        /// This corresponds to [`i2d_NETSCAPE_SPKI`].
+        ///
+        /// [`i2d_NETSCAPE_SPKI`]: https://www.openssl.org/docs/man1.1.0/crypto/i2d_NETSCAPE_SPKI.html
+        to_der,
+        ffi::i2d_NETSCAPE_SPKI
+    }
+}
+
+/// A builder used to construct an `Spki`.
+pub struct SpkiBuilder(Spki);
+
+impl SpkiBuilder {
+    /// Creates a new builder with an empty challenge.
+    ///
+    /// This corresponds to [`NETSCAPE_SPKI_new`].
+    ///
+    /// [`NETSCAPE_SPKI_new`]: https://www.openssl.org/docs/man1.1.0/crypto/NETSCAPE_SPKI_new.html
+    pub fn new() -> Result<SpkiBuilder, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::NETSCAPE_SPKI_new()).map(|p| SpkiBuilder(Spki(p)))
+        }
+    }
+
+    /// Sets the public key.
+    ///
+    /// This corresponds to [`NETSCAPE_SPKI_set_pubkey`].
+    ///
+    /// [`NETSCAPE_SPKI_set_pubkey`]: https://www.openssl.org/docs/man1.1.0/crypto/NETSCAPE_SPKI_set_pubkey.html
+    pub fn set_pubkey<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe { cvt(ffi::NETSCAPE_SPKI_set_pubkey(self.0.as_ptr(), key.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the challenge string.
+    pub fn set_challenge(&mut self, challenge: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(challenge.len() <= c_int::max_value() as usize);
+            let spkac = (*self.0.as_ptr()).spkac;
+            cvt(ffi::ASN1_STRING_set(
+                (*spkac).challenge,
+                challenge.as_ptr() as *const _,
+                challenge.len() as c_int,
+            )).map(|_| ())
+        }
+    }
+
+    /// Signs the SPKAC with a private key.
+    ///
+    /// This corresponds to [`NETSCAPE_SPKI_sign`].
+    ///
+    /// [`NETSCAPE_SPKI_sign`]: https://www.openssl.org/docs/man1.1.0/crypto/NETSCAPE_SPKI_sign.html
+    pub fn sign<T>(&mut self, key: &PKeyRef<T>, hash: MessageDigest) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            cvt(ffi::NETSCAPE_SPKI_sign(
+                self.0.as_ptr(),
+                key.as_ptr(),
+                hash.as_ptr(),
+            )).map(|_| ())
+        }
+    }
+
+    /// Consumes the builder, returning the SPKAC.
+    pub fn build(self) -> Spki {
+        self.0
+    }
+}
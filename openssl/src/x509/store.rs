@@ -45,7 +45,7 @@ use foreign_types::ForeignTypeRef;
 use std::mem;
 
 use error::ErrorStack;
-use x509::X509;
+use x509::{X509Purpose, X509Trust, X509};
 use {cvt, cvt_p};
 
 foreign_type_and_impl_send_sync! {
@@ -93,6 +93,27 @@ impl X509StoreBuilderRef {
     pub fn set_default_paths(&mut self) -> Result<(), ErrorStack> {
         unsafe { cvt(ffi::X509_STORE_set_default_paths(self.as_ptr())).map(|_| ()) }
     }
+
+    /// Sets the purpose that certificates are verified for.
+    ///
+    /// This corresponds to [`X509_STORE_set_purpose`].
+    ///
+    /// [`X509_STORE_set_purpose`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_STORE_set_purpose.html
+    pub fn set_purpose(&mut self, purpose: X509Purpose) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_STORE_set_purpose(self.as_ptr(), purpose.as_raw())).map(|_| ()) }
+    }
+
+    /// Sets the trust setting that trust anchors are checked against.
+    ///
+    /// Trust anchors carrying auxiliary trust information (for example those loaded with
+    /// `X509::from_trusted_pem`) are only accepted if they are trusted for this setting.
+    ///
+    /// This corresponds to [`X509_STORE_set_trust`].
+    ///
+    /// [`X509_STORE_set_trust`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_STORE_set_trust.html
+    pub fn set_trust(&mut self, trust: X509Trust) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_STORE_set_trust(self.as_ptr(), trust.as_raw())).map(|_| ()) }
+    }
 }
 
 foreign_type_and_impl_send_sync! {
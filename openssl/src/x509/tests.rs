@@ -1,6 +1,6 @@
 use hex::{self, FromHex};
 
-use asn1::Asn1Time;
+use asn1::{Asn1Object, Asn1Time};
 use bn::{BigNum, MsbOption};
 use hash::MessageDigest;
 use nid::Nid;
@@ -11,8 +11,12 @@ use x509::extension::{
     AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
     SubjectKeyIdentifier,
 };
+use x509::spki::{Spki, SpkiBuilder};
 use x509::store::X509StoreBuilder;
-use x509::{X509, X509Name, X509Req, X509StoreContext, X509VerifyResult};
+use x509::{
+    X509, X509Builder, X509Name, X509Req, X509StoreContext, X509Trust, X509TrustResult,
+    X509VerifyResult,
+};
 
 fn pkey() -> PKey<Private> {
     let rsa = Rsa::generate(2048).unwrap();
@@ -363,3 +367,245 @@ fn test_verify_fails() {
         .init(&store, &cert, &chain, |c| c.verify_cert())
         .unwrap());
 }
+
+#[test]
+fn trusted_pem() {
+    let root = include_bytes!("../../test/root-ca.pem");
+    let root = X509::from_pem(root).unwrap();
+    assert_eq!(root.alias(), None);
+    assert_eq!(root.keyid(), None);
+
+    let mut ca = X509Builder::from_certificate(&root).unwrap();
+    ca.set_alias(b"foobar").unwrap();
+    ca.set_keyid(b"\x01\x02\x03").unwrap();
+    ca.add_trust_object(&Asn1Object::from_nid(Nid::CLIENT_AUTH).unwrap())
+        .unwrap();
+    ca.add_reject_object(&Asn1Object::from_nid(Nid::SERVER_AUTH).unwrap())
+        .unwrap();
+    let ca = ca.build();
+    // the original certificate is unaffected
+    assert_eq!(root.alias(), None);
+
+    let pem = ca.to_trusted_pem().unwrap();
+    assert!(pem.starts_with(b"-----BEGIN TRUSTED CERTIFICATE-----"));
+
+    let ca = X509::from_trusted_pem(&pem).unwrap();
+    assert_eq!(ca.alias(), Some(&b"foobar"[..]));
+    assert_eq!(ca.keyid(), Some(&b"\x01\x02\x03"[..]));
+    assert_eq!(ca.check_trust(X509Trust::SSL_CLIENT), X509TrustResult::TRUSTED);
+    assert_eq!(ca.check_trust(X509Trust::SSL_SERVER), X509TrustResult::REJECTED);
+
+    #[cfg(ossl110)]
+    {
+        let trust = ca.trust_objects().unwrap();
+        assert_eq!(trust.len(), 1);
+        assert_eq!(trust.get(0).unwrap().nid(), Nid::CLIENT_AUTH);
+        let reject = ca.reject_objects().unwrap();
+        assert_eq!(reject.len(), 1);
+        assert_eq!(reject.get(0).unwrap().nid(), Nid::SERVER_AUTH);
+    }
+
+    let cert = X509::from_trusted_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    assert_eq!(cert.alias(), None);
+}
+
+#[test]
+fn verify_client_auth_only_root() {
+    let cert = include_bytes!("../../test/cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let ca = include_bytes!("../../test/root-ca.pem");
+    let ca = X509::from_pem(ca).unwrap();
+    let mut ca = X509Builder::from_certificate(&ca).unwrap();
+    ca.add_trust_object(&Asn1Object::from_nid(Nid::CLIENT_AUTH).unwrap())
+        .unwrap();
+    let ca = ca.build();
+    let chain = Stack::new().unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(ca.clone()).unwrap();
+    store_bldr.set_trust(X509Trust::SSL_CLIENT).unwrap();
+    let store = store_bldr.build();
+
+    let mut context = X509StoreContext::new().unwrap();
+    assert!(
+        context
+            .init(&store, &cert, &chain, |c| c.verify_cert())
+            .unwrap()
+    );
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(ca).unwrap();
+    store_bldr.set_trust(X509Trust::SSL_SERVER).unwrap();
+    let store = store_bldr.build();
+
+    let mut context = X509StoreContext::new().unwrap();
+    let error = context
+        .init(&store, &cert, &chain, |c| {
+            assert!(!c.verify_cert()?);
+            Ok(c.error())
+        })
+        .unwrap();
+    assert_eq!(error, X509VerifyResult::CERT_REJECTED);
+}
+
+#[test]
+fn spki_parse() {
+    let spkac = include_str!("../../test/spkac.txt");
+    let spki = Spki::from_base64(spkac.trim()).unwrap();
+
+    assert_eq!(spki.challenge().as_slice(), b"hello");
+
+    let key = include_bytes!("../../test/key.pem");
+    let key = PKey::private_key_from_pem(key).unwrap();
+    assert!(spki.public_key().unwrap().public_eq(&key));
+    assert!(spki.verify(&key).unwrap());
+    assert!(!spki.verify(&pkey()).unwrap());
+
+    let der = spki.to_der().unwrap();
+    let spki = Spki::from_der(&der).unwrap();
+    assert_eq!(spki.challenge().as_slice(), b"hello");
+
+    assert!(Spki::from_base64("").is_err());
+}
+
+#[test]
+fn spki_builder() {
+    let pkey = pkey();
+
+    let mut builder = SpkiBuilder::new().unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder.set_challenge("foobar").unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let spki = builder.build();
+
+    let encoded = spki.to_base64().unwrap();
+    let spki = Spki::from_base64(&encoded).unwrap();
+    assert_eq!(&**spki.challenge().as_utf8().unwrap(), "foobar");
+    assert!(spki.public_key().unwrap().public_eq(&pkey));
+    assert!(spki.verify(&pkey).unwrap());
+}
+
+#[test]
+fn x509_builder_from_certificate() {
+    let pkey = pkey();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "foobar.com")
+        .unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(30).unwrap())
+        .unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    let serial = BigNum::from_u32(42).unwrap();
+    builder
+        .set_serial_number(&serial.to_asn1_integer().unwrap())
+        .unwrap();
+    let basic_constraints = BasicConstraints::new().critical().ca().build().unwrap();
+    builder.append_extension(basic_constraints).unwrap();
+    let key_usage = KeyUsage::new().key_cert_sign().build().unwrap();
+    builder.append_extension(key_usage).unwrap();
+    let subject_alternative_name = SubjectAlternativeName::new()
+        .dns("example.com")
+        .build(&builder.x509v3_context(None, None))
+        .unwrap();
+    builder.append_extension(subject_alternative_name).unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let old = builder.build();
+
+    let mut builder = X509Builder::from_certificate(&old).unwrap();
+    let not_after = Asn1Time::days_from_now(365).unwrap();
+    builder.set_not_after(&not_after).unwrap();
+    let subject_alternative_name = SubjectAlternativeName::new()
+        .dns("example.org")
+        .build(&builder.x509v3_context(None, None))
+        .unwrap();
+    builder.replace_extension(subject_alternative_name).unwrap();
+    assert!(builder.remove_extension(Nid::KEY_USAGE));
+    assert!(!builder.remove_extension(Nid::KEY_USAGE));
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let new = builder.build();
+
+    assert_eq!(old.issued(&new), X509VerifyResult::OK);
+    let cn = new.subject_name().entries_by_nid(Nid::COMMONNAME).next().unwrap();
+    assert_eq!(cn.data().as_slice(), b"foobar.com");
+    assert_eq!(
+        new.serial_number().to_bn().unwrap(),
+        old.serial_number().to_bn().unwrap()
+    );
+    assert_eq!(new.not_before().to_string(), old.not_before().to_string());
+    assert_eq!(new.not_after().to_string(), not_after.to_string());
+    let names = new.subject_alt_names().unwrap();
+    assert_eq!(names.len(), 1);
+    assert_eq!(names[0].dnsname(), Some("example.org"));
+
+    let mut builder = X509Builder::from_certificate(&new).unwrap();
+    assert!(builder.remove_extension(Nid::BASIC_CONSTRAINTS));
+    assert!(builder.remove_extension(Nid::SUBJECT_ALT_NAME));
+    assert!(!builder.remove_extension(Nid::KEY_USAGE));
+}
+
+#[test]
+fn x509_req_attributes() {
+    let pkey = pkey();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "foobar.com")
+        .unwrap();
+    let name = name.build();
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_version(0).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder
+        .add_attribute_by_nid(Nid::PKCS9_CHALLENGEPASSWORD, "hunter2")
+        .unwrap();
+    builder
+        .add_attribute_by_nid(Nid::PKCS9_UNSTRUCTUREDNAME, "device 1")
+        .unwrap();
+    builder.add_attribute_by_text("1.2.3.4", "custom").unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+
+    let pem = builder.build().to_pem().unwrap();
+    let req = X509Req::from_pem(&pem).unwrap();
+
+    assert_eq!(req.attributes().count(), 3);
+
+    let password = req
+        .attributes_by_nid(Nid::PKCS9_CHALLENGEPASSWORD)
+        .next()
+        .unwrap();
+    assert_eq!(password.object().nid(), Nid::PKCS9_CHALLENGEPASSWORD);
+    assert_eq!(password.value_count(), 1);
+    assert_eq!(password.value(0).unwrap().as_slice(), b"hunter2");
+    assert!(password.value(1).is_none());
+
+    let name = req
+        .attributes_by_nid(Nid::PKCS9_UNSTRUCTUREDNAME)
+        .next()
+        .unwrap();
+    assert_eq!(name.value(0).unwrap().as_slice(), b"device 1");
+
+    let custom = req
+        .attributes()
+        .find(|a| a.object().to_string() == "1.2.3.4")
+        .unwrap();
+    assert_eq!(&**custom.value(0).unwrap().as_utf8().unwrap(), "custom");
+
+    assert_eq!(
+        req.signature_algorithm().object().nid(),
+        Nid::SHA256WITHRSAENCRYPTION
+    );
+    assert!(!req.signature().as_slice().is_empty());
+    assert!(req.verify(&req.public_key().unwrap()).unwrap());
+    assert!(!req.verify(&self::pkey()).unwrap());
+}
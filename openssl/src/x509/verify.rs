@@ -5,6 +5,7 @@ use std::net::IpAddr;
 
 use cvt;
 use error::ErrorStack;
+use x509::{X509Purpose, X509Trust};
 
 bitflags! {
     /// Flags used to check an `X509` certificate.
@@ -45,6 +46,29 @@ impl X509VerifyParamRef {
         }
     }
 
+    /// Sets the purpose that certificates are verified for.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set_purpose`].
+    ///
+    /// [`X509_VERIFY_PARAM_set_purpose`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_set_purpose.html
+    pub fn set_purpose(&mut self, purpose: X509Purpose) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set_purpose(
+                self.as_ptr(),
+                purpose.as_raw(),
+            )).map(|_| ())
+        }
+    }
+
+    /// Sets the trust setting that trust anchors are checked against.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set_trust`].
+    ///
+    /// [`X509_VERIFY_PARAM_set_trust`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_set_trust.html
+    pub fn set_trust(&mut self, trust: X509Trust) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_VERIFY_PARAM_set_trust(self.as_ptr(), trust.as_raw())).map(|_| ()) }
+    }
+
     /// Set the expected DNS hostname.
     ///
     /// This corresponds to [`X509_VERIFY_PARAM_set1_host`].